@@ -0,0 +1,75 @@
+use num_complex::Complex;
+use num_traits::{Float, FloatConst};
+
+use crate::util;
+
+/// In-place radix-2 FFT, `X[n] = Σ x[k]·e^(-2πikn/len)`. The length must be a power of two.
+pub fn fft<F>(x: &mut [Complex<F>])
+where
+    F: Float + FloatConst
+{
+    let len = x.len();
+    assert!(len.is_power_of_two(), "FFT length must be a power of two.");
+    if len <= 1
+    {
+        return
+    }
+
+    let bits = len.trailing_zeros();
+    for k in 0..len
+    {
+        let j = k.reverse_bits() >> (usize::BITS - bits);
+        if k < j
+        {
+            x.swap(k, j);
+        }
+    }
+
+    let mut m = 2;
+    while m <= len
+    {
+        let w_m = Complex::cis(-F::TAU()/F::from(m).unwrap());
+        for chunk in x.chunks_exact_mut(m)
+        {
+            let (lo, hi) = chunk.split_at_mut(m/2);
+            let mut w = Complex::new(F::one(), F::zero());
+            for (u, v) in lo.iter_mut().zip(hi.iter_mut())
+            {
+                let t = w**v;
+                *v = *u - t;
+                *u = *u + t;
+                util::mul_assign(&mut w, w_m);
+            }
+        }
+        m *= 2;
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f64::consts::TAU;
+
+    use num_complex::Complex;
+
+    use super::fft;
+
+    #[test]
+    fn matches_dft()
+    {
+        const LEN: usize = 64;
+
+        let x: [Complex<f64>; LEN] = core::array::from_fn(|k| Complex::new((k as f64*0.37).sin(), (k as f64*1.3).cos()));
+        let mut y = x;
+        fft(&mut y);
+
+        for (n, y) in y.into_iter().enumerate()
+        {
+            let dft: Complex<f64> = x.iter()
+                .enumerate()
+                .map(|(k, &x)| x*Complex::cis(-TAU*(k*n) as f64/LEN as f64))
+                .sum();
+            assert!((y - dft).norm() < 1e-9);
+        }
+    }
+}
@@ -3,18 +3,26 @@
 #![feature(let_chains)]
 #![feature(iter_array_chunks)]
 #![feature(specialization)]
+#![feature(portable_simd)]
 
 use num_traits::Float;
 
+#[doc(hidden)]
+pub use num_traits;
+
 moddef::moddef!(
     flat(pub) mod {
         wavetable
     },
     pub mod {
         oscillator,
+        preset,
+        simd,
+        wav,
         waveform
     },
     mod {
+        fft,
         plot for cfg(test),
         util
     }
@@ -0,0 +1,298 @@
+use num_traits::{Euclid, Float, FloatConst};
+
+use crate::Wavetable;
+
+/// Partials of an idealized tuned church bell: hum, prime, tierce, quint, nominal and upper partials, relative to the prime.
+const BELL: [f64; 12] = [0.5, 1.0, 1.2, 1.5, 2.0, 2.5, 2.667, 3.0, 4.0, 5.333, 6.667, 8.0];
+/// `βL` of the first mode of a free-free bar. The rest are close to `(2n + 1)π/2`.
+const BAR_FIRST_MODE: f64 = 4.7300408;
+/// Where partials start fading out, relative to Nyquist.
+const NYQUIST_FADE: f64 = 0.9;
+
+/// Models for the frequency ratios of the partials of an [`Additive`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+pub enum Inharmonicity<F>
+{
+    /// `n`
+    #[default]
+    Harmonic,
+    /// `n·√(1 + B·n²)`, for a stiff string like in a piano, with inharmonicity coefficient `B`.
+    Stiff(F),
+    /// An idealized tuned church bell. Past its twelve partials, they keep going an octave per four partials.
+    Bell,
+    /// The modes of a free metal bar, like in a glockenspiel.
+    Metal
+}
+
+impl<F> Inharmonicity<F>
+where
+    F: Float + FloatConst
+{
+    /// The frequency ratio of partial `n`, counting from `1`.
+    pub fn ratio(self, n: usize) -> F
+    {
+        let one = F::one();
+        let n_f = F::from(n).unwrap();
+        match self
+        {
+            Self::Harmonic => n_f,
+            Self::Stiff(b) => n_f*(one + b*n_f*n_f).sqrt(),
+            Self::Bell => match BELL.get(n.saturating_sub(1))
+            {
+                Some(&ratio) => F::from(ratio).unwrap(),
+                None => {
+                    let octaves = F::from(n - BELL.len()).unwrap()/F::from(4.0).unwrap();
+                    F::from(BELL[BELL.len() - 1]).unwrap()*octaves.exp2()
+                }
+            },
+            Self::Metal => {
+                let first = F::from(BAR_FIRST_MODE).unwrap();
+                let beta = if n <= 1 {first} else {(n_f + n_f + one)*F::FRAC_PI_2()};
+                (beta/first).powi(2)
+            }
+        }
+    }
+}
+
+/// One sinusoid of an [`Additive`], `amplitude·cos(θ + phase)` at `ratio` times the frequency.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+pub struct Partial<F>
+{
+    pub ratio: F,
+    pub amplitude: F,
+    pub phase: F,
+    theta: F,
+    gain: F
+}
+
+impl<F> Partial<F>
+where
+    F: Float
+{
+    pub fn new(ratio: F, amplitude: F, phase: F) -> Self
+    {
+        Self {
+            ratio,
+            amplitude,
+            phase,
+            theta: F::zero(),
+            gain: amplitude
+        }
+    }
+
+    pub fn theta(&self) -> F
+    {
+        self.theta
+    }
+
+    /// The amplitude it's playing at, on its way towards `amplitude`, before fading out near Nyquist.
+    pub fn gain(&self) -> F
+    {
+        self.gain
+    }
+}
+
+/// An additive oscillator of `P` sinusoidal partials, at any frequency ratios.
+///
+/// Partials fade out linearly from 90% of Nyquist, and are silenced at or above it, so sweeping the frequency doesn't click.
+/// Amplitude changes, and partials coming back below Nyquist, are smoothed with a time constant of `smoothing` seconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Additive<F, const P: usize>
+{
+    pub omega: F,
+    /// Time constant of the amplitude smoothing, in seconds.
+    pub smoothing: F,
+    partials: [Partial<F>; P]
+}
+
+impl<F, const P: usize> Additive<F, P>
+where
+    F: Float + FloatConst + Euclid
+{
+    pub fn new(omega: F, partials: [Partial<F>; P]) -> Self
+    {
+        Self {
+            omega,
+            smoothing: F::zero(),
+            partials
+        }
+    }
+
+    /// The harmonics of a wavetable as partials. The DC-offset is left out.
+    pub fn from_wavetable(omega: F, wavetable: &Wavetable<F, P>) -> Self
+    {
+        Self::new(omega, core::array::from_fn(|m| {
+            let (r, phi) = wavetable.polar(m + 1).unwrap();
+            Partial::new(F::from(m + 1).unwrap(), r, -phi)
+        }))
+    }
+
+    /// `P` partials with ratios from an inharmonicity model, with amplitudes from `amplitude(n)`, counting from `1`.
+    pub fn from_inharmonicity(omega: F, inharmonicity: Inharmonicity<F>, mut amplitude: impl FnMut(usize) -> F) -> Self
+    {
+        let zero = F::zero();
+        Self::new(omega, core::array::from_fn(|m| Partial::new(inharmonicity.ratio(m + 1), amplitude(m + 1), zero)))
+    }
+
+    pub fn partials(&self) -> &[Partial<F>; P]
+    {
+        &self.partials
+    }
+    pub fn partials_mut(&mut self) -> &mut [Partial<F>; P]
+    {
+        &mut self.partials
+    }
+
+    /// Retunes the partials to an inharmonicity model, keeping their amplitudes and phases.
+    pub fn set_inharmonicity(&mut self, inharmonicity: Inharmonicity<F>)
+    {
+        for (m, partial) in self.partials.iter_mut().enumerate()
+        {
+            partial.ratio = inharmonicity.ratio(m + 1)
+        }
+    }
+
+    /// Starts every partial over at phase zero, at its amplitude.
+    pub fn reset(&mut self)
+    {
+        for partial in self.partials.iter_mut()
+        {
+            partial.theta = F::zero();
+            partial.gain = partial.amplitude;
+        }
+    }
+
+    pub fn next(&mut self, rate: F) -> F
+    {
+        let zero = F::zero();
+        let one = F::one();
+        let tau = F::TAU();
+        let nyq = F::PI()*rate;
+        let fade_from = F::from(NYQUIST_FADE).unwrap()*nyq;
+
+        let coefficient = if self.smoothing > zero
+        {
+            one - (-(self.smoothing*rate).recip()).exp()
+        }
+        else
+        {
+            one
+        };
+
+        let mut y = zero;
+        for partial in self.partials.iter_mut()
+        {
+            let omega = self.omega*partial.ratio;
+            partial.theta = (partial.theta + omega/rate).rem_euclid(&tau);
+            if omega.abs() >= nyq
+            {
+                partial.gain = zero;
+                continue
+            }
+
+            partial.gain = partial.gain + (partial.amplitude - partial.gain)*coefficient;
+            let fade = ((nyq - omega.abs())/(nyq - fade_from)).min(one);
+            if partial.gain != zero
+            {
+                y = y + partial.gain*fade*(partial.theta + partial.phase).cos();
+            }
+        }
+        y
+    }
+
+    /// Fills `block` with the output.
+    pub fn process(&mut self, rate: F, block: &mut [F])
+    {
+        for y in block.iter_mut()
+        {
+            *y = self.next(rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f64::consts::TAU;
+
+    use crate::{
+        oscillator::{Oscillator, Wave},
+        waveform::{Sawtooth, Waveform},
+        Wavetable
+    };
+
+    use super::{Additive, Inharmonicity, Partial};
+
+    const RATE: f64 = 48000.0;
+
+    #[test]
+    fn from_wavetable()
+    {
+        // Low enough for the wavetable to be used, and high enough for some harmonics to be culled, with none fading out.
+        let omega = TAU*2680.0;
+        let wavetable: Wavetable<f64, 32> = Sawtooth.wavetable().unwrap();
+
+        let mut additive = Additive::from_wavetable(omega, &wavetable);
+        let mut wave = Oscillator::new(omega, 0.0, Wave::<_, _, 32>::from(Sawtooth));
+        for _ in 0..256
+        {
+            assert!((additive.next(RATE) - (wave.next(RATE) - wavetable.a0())).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn culling_and_smoothing()
+    {
+        let omega = TAU*1000.0;
+        let mut additive = Additive::new(omega, [Partial::new(1.0, 1.0, 0.0), Partial::new(30.0, 1.0, 0.0)]);
+        for _ in 0..64
+        {
+            let y = additive.next(RATE);
+            assert!((y - additive.partials()[0].theta().cos()).abs() < 1e-12);
+        }
+        assert_eq!(additive.partials()[1].gain(), 0.0);
+
+        additive.smoothing = 0.01;
+        additive.partials_mut()[0].amplitude = 0.0;
+        let mut block = [0.0; 480];
+        additive.process(RATE, &mut block);
+        let gain = additive.partials()[0].gain();
+        assert!((gain - (-1.0f64).exp()).abs() < 1e-6);
+        assert!(block.iter().all(|y| y.abs() <= 1.0));
+    }
+
+    #[test]
+    fn nyquist_sweep()
+    {
+        let nyq = RATE/2.0;
+        let mut additive = Additive::new(0.0, [Partial::new(1.0, 1.0, 0.0)]);
+        for k in 0..1000
+        {
+            let frequency = nyq*(0.8 + 0.3*k as f64/1000.0);
+            additive.omega = TAU*frequency;
+            let y = additive.next(RATE);
+            let fade = ((nyq - frequency)/(0.1*nyq)).clamp(0.0, 1.0);
+            assert!((y - fade*additive.partials()[0].theta().cos()).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn inharmonicity()
+    {
+        assert_eq!(Inharmonicity::<f64>::Harmonic.ratio(3), 3.0);
+        assert!((Inharmonicity::Stiff(1e-3).ratio(10) - 10.0*1.1f64.sqrt()).abs() < 1e-12);
+        assert_eq!(Inharmonicity::<f64>::Bell.ratio(2), 1.0);
+        assert!((Inharmonicity::<f64>::Bell.ratio(16) - 16.0).abs() < 1e-12);
+        for (n, ratio) in [(1, 1.0), (2, 2.7565), (3, 5.4039), (4, 8.9330)]
+        {
+            assert!((Inharmonicity::<f64>::Metal.ratio(n) - ratio).abs() < 1e-3);
+        }
+
+        let mut bell = Additive::<f64, 12>::from_inharmonicity(TAU*440.0, Inharmonicity::Bell, |n| 1.0/n as f64);
+        bell.set_inharmonicity(Inharmonicity::Metal);
+        assert_eq!(bell.partials()[1].amplitude, 0.5);
+        let mut block = [0.0; 64];
+        bell.process(RATE, &mut block);
+        assert!(block.iter().all(|y| y.is_finite()));
+    }
+}
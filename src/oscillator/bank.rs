@@ -0,0 +1,181 @@
+use core::simd::{Simd, SimdElement};
+
+use num_traits::{Float, FloatConst};
+
+use crate::{
+    simd::FloatLanes,
+    waveform::{SimdWaveform, Waveform},
+    Wavetable, WavetableView
+};
+
+/// `L` oscillators sharing one waveform, advanced in lock-step with [`core::simd`].
+///
+/// With `N != 0` the lanes are band-limited through a shared [`Wavetable`] the same way [`Wave`](super::Wave) does it, and
+/// with `N == 0` the naive waveform is used, like with [`Direct`](super::Direct).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OscillatorBank<F, W, const L: usize, const N: usize>
+where
+    F: Float + FloatConst + SimdElement,
+    W: Waveform<F>
+{
+    pub omega: Simd<F, L>,
+    pub phi: Simd<F, L>,
+    theta: Simd<F, L>,
+    waveform: W,
+    wavetable: Option<Option<Wavetable<F, N>>>
+}
+
+impl<F, W, const L: usize, const N: usize> OscillatorBank<F, W, L, N>
+where
+    F: Float + FloatConst + SimdElement,
+    W: Waveform<F>
+{
+    pub fn new(omega: [F; L], phi: [F; L], waveform: W) -> Self
+    {
+        Self {
+            omega: Simd::from_array(omega),
+            phi: Simd::from_array(phi),
+            theta: Simd::splat(F::zero()),
+            waveform,
+            wavetable: None
+        }
+    }
+
+    pub fn theta(&self) -> Simd<F, L>
+    {
+        self.theta
+    }
+
+    pub fn waveform(&self) -> &W
+    {
+        &self.waveform
+    }
+    pub fn waveform_mut(&mut self) -> &mut W
+    {
+        self.wavetable = None;
+        &mut self.waveform
+    }
+
+    pub fn wavetable(&self) -> Option<WavetableView<'_, F>>
+    {
+        self.wavetable.as_ref().and_then(|w| w.as_ref().map(Wavetable::view))
+    }
+
+    pub fn next(&mut self, rate: F) -> Simd<F, L>
+    where
+        Simd<F, L>: FloatLanes<F, L>
+    {
+        let zero = F::zero();
+        let pi = F::PI();
+        let tau = F::TAU();
+        let nyq = pi*rate;
+
+        self.theta = (self.theta + self.omega/Simd::splat(rate)).rem_euclid(Simd::splat(tau));
+        let theta = self.theta + self.phi;
+
+        let wavetable = if N != 0
+        {
+            self.wavetable.get_or_insert_with(|| self.waveform.wavetable()).as_ref()
+        }
+        else
+        {
+            None
+        };
+
+        let mut muted = [false; L];
+        let mut up_to = [0; L];
+        let mut naive = [wavetable.is_none(); L];
+        for (l, omega) in self.omega.to_array().into_iter().enumerate()
+        {
+            if nyq <= omega
+            {
+                muted[l] = true;
+                naive[l] = false;
+            }
+            else if wavetable.is_some()
+            {
+                up_to[l] = (nyq/omega).abs().to_usize().unwrap_or(N);
+                naive[l] = up_to[l] > N;
+            }
+        }
+
+        let mut y = wavetable.map(|wavetable| wavetable.waveform_simd(theta, up_to).to_array())
+            .unwrap_or([zero; L]);
+        for (y, naive) in y.iter().zip(naive.iter_mut())
+        {
+            *naive |= !y.is_finite();
+        }
+        let y_naive = naive.contains(&true)
+            .then(|| self.waveform.waveform_simd(theta).to_array());
+
+        for l in 0..L
+        {
+            if muted[l]
+            {
+                y[l] = zero
+            }
+            else if naive[l] && let Some(y_naive) = &y_naive
+            {
+                y[l] = y_naive[l]
+            }
+        }
+        Simd::from_array(y)
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f32::consts::TAU;
+
+    use crate::{
+        oscillator::{Direct, Oscillator, Wave, WaveDTC},
+        waveform::{Sawtooth, Square, Triangle}
+    };
+
+    use super::OscillatorBank;
+
+    const L: usize = 8;
+    const RATE: f32 = 8000.0;
+    const TOLERANCE: f32 = 1e-3;
+
+    #[test]
+    fn matches_scalar()
+    {
+        let omega: [f32; L] = core::array::from_fn(|l| TAU*(55.0 + 110.0*l as f32*l as f32));
+        let phi: [f32; L] = core::array::from_fn(|l| l as f32*0.3);
+
+        let mut bank = OscillatorBank::<_, _, L, 64>::new(omega, phi, Sawtooth);
+        let mut oscs = core::array::from_fn::<_, L, _>(|l| Oscillator::new(omega[l], phi[l], Wave::<_, _, 64>::from(Sawtooth)));
+        for _ in 0..256
+        {
+            let y = bank.next(RATE).to_array();
+            for (y, osc) in y.into_iter().zip(oscs.iter_mut())
+            {
+                assert!((y - osc.next(RATE)).abs() < TOLERANCE);
+            }
+        }
+
+        let mut bank = OscillatorBank::<_, _, L, 64>::new(omega, phi, Square);
+        let mut oscs = core::array::from_fn::<_, L, _>(|l| Oscillator::new(omega[l], phi[l], WaveDTC::<_, _, 64>::from(Square)));
+        for _ in 0..256
+        {
+            let y = bank.next(RATE).to_array();
+            for (y, osc) in y.into_iter().zip(oscs.iter_mut())
+            {
+                assert!((y - osc.next(RATE)).abs() < TOLERANCE);
+            }
+        }
+
+        let mut bank = OscillatorBank::<_, _, L, 0>::new(omega, phi, Triangle);
+        let mut oscs = core::array::from_fn::<_, L, _>(|l| Oscillator::new(omega[l], phi[l], Direct::from(Triangle)));
+        for _ in 0..256
+        {
+            let y = bank.next(RATE).to_array();
+            for (y, osc) in y.into_iter().zip(oscs.iter_mut())
+            {
+                assert!((y - osc.next(RATE)).abs() < TOLERANCE);
+            }
+        }
+    }
+}
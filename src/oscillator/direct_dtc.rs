@@ -69,6 +69,10 @@ where
     {
         self.duty_cycle
     }
+    fn set_duty_cycle(&mut self, duty_cycle: F)
+    {
+        self.duty_cycle = duty_cycle
+    }
 
     fn waveform(&self) -> &Self::Waveform
     {
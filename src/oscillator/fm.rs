@@ -0,0 +1,367 @@
+use num_traits::{Euclid, Float, FloatConst};
+
+use crate::waveform::Sine;
+
+use super::{Direct, Oscillator};
+
+/// The frequency of an [`Operator`].
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+pub enum OperatorFrequency<F>
+{
+    /// Relative to the angular frequency of the [`Fm`].
+    Ratio(F),
+    /// Angular frequency, in radians per second.
+    Fixed(F)
+}
+
+impl<F> OperatorFrequency<F>
+where
+    F: Float
+{
+    pub fn omega(self, omega: F) -> F
+    {
+        match self
+        {
+            Self::Ratio(ratio) => ratio*omega,
+            Self::Fixed(omega) => omega
+        }
+    }
+}
+
+impl<F> Default for OperatorFrequency<F>
+where
+    F: Float
+{
+    fn default() -> Self
+    {
+        Self::Ratio(F::one())
+    }
+}
+
+/// A sine oscillator in an [`Fm`], phase-modulated by the other operators.
+///
+/// The output is scaled by `level`, which is also the modulation index, in radians, of the operators it modulates. Feedback
+/// is the average of the last two outputs of the operator it comes from, scaled by `feedback`, like on the DX7. The frequency and
+/// phase offset of its oscillator are set by the [`Fm`] every sample, so only its phase is exposed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Operator<F>
+where
+    F: Float + FloatConst + Euclid
+{
+    pub frequency: OperatorFrequency<F>,
+    pub level: F,
+    pub feedback: F,
+    oscillator: Oscillator<F, Direct<Sine>>,
+    history: [F; 2]
+}
+
+impl<F> Operator<F>
+where
+    F: Float + FloatConst + Euclid
+{
+    pub fn new(frequency: OperatorFrequency<F>, level: F) -> Self
+    {
+        let zero = F::zero();
+        Self {
+            frequency,
+            level,
+            feedback: zero,
+            oscillator: Oscillator::new(zero, zero, Direct::from(Sine)),
+            history: [zero; 2]
+        }
+    }
+
+    pub fn theta(&self) -> F
+    {
+        self.oscillator.theta()
+    }
+    pub fn set_theta(&mut self, theta: F)
+    {
+        self.oscillator.set_theta(theta)
+    }
+
+    /// The output of the last sample.
+    pub fn output(&self) -> F
+    {
+        self.history[0]
+    }
+}
+
+impl<F> Default for Operator<F>
+where
+    F: Float + FloatConst + Euclid
+{
+    fn default() -> Self
+    {
+        Self::new(OperatorFrequency::default(), F::one())
+    }
+}
+
+/// How the operators of an [`Fm`] are connected.
+///
+/// Operators are computed from last to first, so operator `j` modulates operator `i` in the same sample if `j > i`, and with the
+/// output of the previous sample otherwise.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Algorithm<F, const M: usize>
+{
+    /// How much operator `j` modulates operator `i`, at `modulation[i][j]`.
+    pub modulation: [[F; M]; M],
+    /// Which operator's output is fed back into each operator, scaled by its [`feedback`](Operator::feedback).
+    pub feedback: [Option<usize>; M],
+    /// How much of each operator is heard.
+    pub output: [F; M]
+}
+
+impl<F, const M: usize> Algorithm<F, M>
+where
+    F: Float
+{
+    /// Every operator modulating the one before it, with only the first one heard.
+    pub fn stack() -> Self
+    {
+        let zero = F::zero();
+        let one = F::one();
+        Self {
+            modulation: core::array::from_fn(|i| core::array::from_fn(|j| if j == i + 1 {one} else {zero})),
+            feedback: core::array::from_fn(Some),
+            output: core::array::from_fn(|i| if i == 0 {one} else {zero})
+        }
+    }
+
+    /// Every operator heard, unmodulated.
+    pub fn parallel() -> Self
+    {
+        let zero = F::zero();
+        Self {
+            modulation: [[zero; M]; M],
+            feedback: core::array::from_fn(Some),
+            output: [F::one(); M]
+        }
+    }
+}
+
+impl<F> Algorithm<F, 6>
+where
+    F: Float
+{
+    /// The DX7 algorithm with the given number, from `1` to `32`, with operator `k` on the DX7 at index `k - 1`.
+    pub fn dx7(number: u8) -> Option<Self>
+    {
+        let zero = F::zero();
+        let one = F::one();
+
+        let dx7 = DX7.get((number as usize).checked_sub(1)?)?;
+        let mut algorithm = Self {
+            modulation: [[zero; 6]; 6],
+            feedback: [None; 6],
+            output: [zero; 6]
+        };
+        for &(modulator, carrier) in dx7.modulation
+        {
+            algorithm.modulation[carrier as usize - 1][modulator as usize - 1] = one;
+        }
+        for &carrier in dx7.carriers
+        {
+            algorithm.output[carrier as usize - 1] = one;
+        }
+        let (to, from) = dx7.feedback;
+        algorithm.feedback[to as usize - 1] = Some(from as usize - 1);
+        Some(algorithm)
+    }
+}
+
+/// The connections of a DX7 algorithm, with operators numbered from `1`.
+struct Dx7
+{
+    /// Pairs of `(modulator, modulated)`.
+    modulation: &'static [(u8, u8)],
+    carriers: &'static [u8],
+    /// The operator with feedback, and where it's fed back from.
+    feedback: (u8, u8)
+}
+
+const DX7: [Dx7; 32] = [
+    Dx7 {modulation: &[(2, 1), (4, 3), (5, 4), (6, 5)], carriers: &[1, 3], feedback: (6, 6)},
+    Dx7 {modulation: &[(2, 1), (4, 3), (5, 4), (6, 5)], carriers: &[1, 3], feedback: (2, 2)},
+    Dx7 {modulation: &[(2, 1), (3, 2), (5, 4), (6, 5)], carriers: &[1, 4], feedback: (6, 6)},
+    Dx7 {modulation: &[(2, 1), (3, 2), (5, 4), (6, 5)], carriers: &[1, 4], feedback: (6, 4)},
+    Dx7 {modulation: &[(2, 1), (4, 3), (6, 5)], carriers: &[1, 3, 5], feedback: (6, 6)},
+    Dx7 {modulation: &[(2, 1), (4, 3), (6, 5)], carriers: &[1, 3, 5], feedback: (6, 5)},
+    Dx7 {modulation: &[(2, 1), (4, 3), (5, 3), (6, 5)], carriers: &[1, 3], feedback: (6, 6)},
+    Dx7 {modulation: &[(2, 1), (4, 3), (5, 3), (6, 5)], carriers: &[1, 3], feedback: (4, 4)},
+    Dx7 {modulation: &[(2, 1), (4, 3), (5, 3), (6, 5)], carriers: &[1, 3], feedback: (2, 2)},
+    Dx7 {modulation: &[(2, 1), (3, 2), (5, 4), (6, 4)], carriers: &[1, 4], feedback: (3, 3)},
+    Dx7 {modulation: &[(2, 1), (3, 2), (5, 4), (6, 4)], carriers: &[1, 4], feedback: (6, 6)},
+    Dx7 {modulation: &[(2, 1), (4, 3), (5, 3), (6, 3)], carriers: &[1, 3], feedback: (2, 2)},
+    Dx7 {modulation: &[(2, 1), (4, 3), (5, 3), (6, 3)], carriers: &[1, 3], feedback: (6, 6)},
+    Dx7 {modulation: &[(2, 1), (4, 3), (5, 4), (6, 4)], carriers: &[1, 3], feedback: (6, 6)},
+    Dx7 {modulation: &[(2, 1), (4, 3), (5, 4), (6, 4)], carriers: &[1, 3], feedback: (2, 2)},
+    Dx7 {modulation: &[(2, 1), (3, 1), (4, 3), (5, 1), (6, 5)], carriers: &[1], feedback: (6, 6)},
+    Dx7 {modulation: &[(2, 1), (3, 1), (4, 3), (5, 1), (6, 5)], carriers: &[1], feedback: (2, 2)},
+    Dx7 {modulation: &[(2, 1), (3, 1), (4, 1), (5, 4), (6, 5)], carriers: &[1], feedback: (3, 3)},
+    Dx7 {modulation: &[(2, 1), (3, 2), (6, 4), (6, 5)], carriers: &[1, 4, 5], feedback: (6, 6)},
+    Dx7 {modulation: &[(3, 1), (3, 2), (5, 4), (6, 4)], carriers: &[1, 2, 4], feedback: (3, 3)},
+    Dx7 {modulation: &[(3, 1), (3, 2), (6, 4), (6, 5)], carriers: &[1, 2, 4, 5], feedback: (3, 3)},
+    Dx7 {modulation: &[(2, 1), (6, 3), (6, 4), (6, 5)], carriers: &[1, 3, 4, 5], feedback: (6, 6)},
+    Dx7 {modulation: &[(3, 2), (6, 4), (6, 5)], carriers: &[1, 2, 4, 5], feedback: (6, 6)},
+    Dx7 {modulation: &[(6, 3), (6, 4), (6, 5)], carriers: &[1, 2, 3, 4, 5], feedback: (6, 6)},
+    Dx7 {modulation: &[(6, 4), (6, 5)], carriers: &[1, 2, 3, 4, 5], feedback: (6, 6)},
+    Dx7 {modulation: &[(3, 2), (5, 4), (6, 4)], carriers: &[1, 2, 4], feedback: (6, 6)},
+    Dx7 {modulation: &[(3, 2), (5, 4), (6, 4)], carriers: &[1, 2, 4], feedback: (3, 3)},
+    Dx7 {modulation: &[(2, 1), (4, 3), (5, 4)], carriers: &[1, 3, 6], feedback: (5, 5)},
+    Dx7 {modulation: &[(4, 3), (6, 5)], carriers: &[1, 2, 3, 5], feedback: (6, 6)},
+    Dx7 {modulation: &[(4, 3), (5, 4)], carriers: &[1, 2, 3, 6], feedback: (5, 5)},
+    Dx7 {modulation: &[(6, 5)], carriers: &[1, 2, 3, 4, 5], feedback: (6, 6)},
+    Dx7 {modulation: &[], carriers: &[1, 2, 3, 4, 5, 6], feedback: (6, 6)}
+];
+
+/// Phase-modulation synthesis with `M` sine [`Operator`]s, connected by an [`Algorithm`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fm<F, const M: usize>
+where
+    F: Float + FloatConst + Euclid
+{
+    pub omega: F,
+    pub operators: [Operator<F>; M],
+    pub algorithm: Algorithm<F, M>
+}
+
+impl<F, const M: usize> Fm<F, M>
+where
+    F: Float + FloatConst + Euclid
+{
+    pub fn new(omega: F, algorithm: Algorithm<F, M>, operators: [Operator<F>; M]) -> Self
+    {
+        Self {
+            omega,
+            operators,
+            algorithm
+        }
+    }
+
+    /// Starts every operator over at phase zero, with no feedback.
+    pub fn reset(&mut self)
+    {
+        let zero = F::zero();
+        for operator in self.operators.iter_mut()
+        {
+            operator.oscillator.set_theta(zero);
+            operator.history = [zero; 2];
+        }
+    }
+
+    pub fn next(&mut self, rate: F) -> F
+    {
+        let zero = F::zero();
+        let two = F::from(2.0).unwrap();
+
+        let mut outputs = self.operators.each_ref().map(Operator::output);
+        for i in (0..M).rev()
+        {
+            let mut phi = self.algorithm.modulation[i].iter()
+                .zip(outputs)
+                .filter(|&(&m, _)| m != zero)
+                .fold(zero, |phi, (&m, y)| phi + m*y);
+            if let Some(j) = self.algorithm.feedback[i]
+                && let Some(source) = self.operators.get(j)
+            {
+                let [y1, y2] = source.history;
+                phi = phi + self.operators[i].feedback*(y1 + y2)/two;
+            }
+
+            let operator = &mut self.operators[i];
+            operator.oscillator.omega = operator.frequency.omega(self.omega);
+            operator.oscillator.phi = phi;
+            outputs[i] = operator.level*operator.oscillator.next(rate);
+        }
+
+        for (operator, y) in self.operators.iter_mut().zip(outputs)
+        {
+            operator.history = [y, operator.history[0]];
+        }
+        self.algorithm.output.iter()
+            .zip(outputs)
+            .fold(zero, |sum, (&g, y)| sum + g*y)
+    }
+
+    /// Fills `block` with the output.
+    pub fn process(&mut self, rate: F, block: &mut [F])
+    {
+        for y in block.iter_mut()
+        {
+            *y = self.next(rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f64::consts::{FRAC_PI_2, TAU};
+
+    use crate::waveform::{PmSine, Waveform};
+
+    use super::{Algorithm, Fm, Operator, OperatorFrequency};
+
+    const RATE: f64 = 48000.0;
+
+    #[test]
+    fn matches_pm_sine()
+    {
+        let omega = TAU*110.0;
+        let pm = PmSine::new(3, 2, 1.5);
+
+        let carrier = Operator::new(OperatorFrequency::Ratio(3.0), 1.0);
+        let mut modulator = Operator::new(OperatorFrequency::Ratio(2.0), 1.5);
+        // The operators are cosines, so start the modulator a quarter period late to make it a sine.
+        modulator.set_theta(-FRAC_PI_2);
+
+        let mut stack = Fm::new(omega, Algorithm::stack(), [carrier, modulator]);
+        let mut dx7 = Fm::new(omega, Algorithm::dx7(1).unwrap(), [Operator::new(OperatorFrequency::Ratio(1.0), 0.0); 6]);
+        dx7.operators[..2].copy_from_slice(&[carrier, modulator]);
+        for k in 1..512
+        {
+            let theta = k as f64*omega/RATE;
+            let y = pm.waveform(theta);
+            assert!((stack.next(RATE) - y).abs() < 1e-9);
+            assert!((dx7.next(RATE) - y).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn algorithms()
+    {
+        assert!(Algorithm::<f64, 6>::dx7(0).is_none());
+        assert!(Algorithm::<f64, 6>::dx7(33).is_none());
+
+        let operators = core::array::from_fn(|k| {
+            let mut operator = Operator::new(OperatorFrequency::Ratio(k as f64 + 1.0), 1.0);
+            operator.feedback = 0.5;
+            operator
+        });
+        for number in 1..=32
+        {
+            let algorithm = Algorithm::dx7(number).unwrap();
+            assert_eq!(algorithm.feedback.iter().flatten().count(), 1);
+            assert!(algorithm.output[0] == 1.0);
+
+            let mut fm = Fm::new(TAU*220.0, algorithm, operators);
+            let mut block = [0.0; 64];
+            fm.process(RATE, &mut block);
+            assert!(block.iter().all(|y| y.is_finite() && y.abs() <= 6.0));
+        }
+
+        let mut additive = Fm::new(TAU*220.0, Algorithm::dx7(32).unwrap(), operators.map(|mut operator| {
+            operator.feedback = 0.0;
+            operator
+        }));
+        let mut parallel = Fm::new(TAU*220.0, Algorithm::parallel(), additive.operators);
+        for k in 1..64
+        {
+            let theta = k as f64*TAU*220.0/RATE;
+            let y = (1..=6).map(|n| (n as f64*theta).cos()).sum::<f64>();
+            assert!((additive.next(RATE) - y).abs() < 1e-9);
+            assert!((parallel.next(RATE) - y).abs() < 1e-9);
+        }
+    }
+}
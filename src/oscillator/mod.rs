@@ -6,10 +6,20 @@ use crate::{waveform::Waveform, WavetableView};
 
 moddef::moddef!(
     flat(pub) mod {
+        additive,
+        bank,
         direct_dtc,
         direct,
+        fm,
+        modulated,
+        scan,
+        switch,
+        unison,
+        vector_wave,
+        voices,
         wave_dtc,
         wave,
+        waveshaper,
     }
 );
 
@@ -35,11 +45,25 @@ where
     }
     
     fn next(&mut self, theta: F, omega: F, rate: F) -> F;
+    /// Like [`OscillatorState::next`], but also gives the quadrature output `ŷ`, the [Hilbert transform](crate::Wavetable::hilbert) of
+    /// the output, when there's a wavetable for it.
+    ///
+    /// At pitches low enough for `next` to fall back on the naive waveform, both come from the harmonics of the wavetable instead,
+    /// so that `ŷ` is there at every pitch.
+    fn next_analytic(&mut self, theta: F, omega: F, rate: F) -> (F, Option<F>)
+    {
+        (self.next(theta, omega, rate), None)
+    }
 
     fn duty_cycle(&self) -> F
     {
         crate::duty_cycle_default()
     }
+    /// Changes the duty cycle in place. Does nothing for states without a duty cycle.
+    fn set_duty_cycle(&mut self, duty_cycle: F)
+    {
+        let _ = duty_cycle;
+    }
 
     fn wavetable(&self) -> Option<WavetableView<'_, F>>
     {
@@ -99,6 +123,15 @@ where
         }
     }
 
+    pub fn theta(&self) -> F
+    {
+        self.theta
+    }
+    pub fn set_theta(&mut self, theta: F)
+    {
+        self.theta = theta
+    }
+
     pub fn next(&mut self, rate: F) -> F
     where
         F: Euclid
@@ -109,6 +142,19 @@ where
         self.state.next(self.theta + self.phi, self.omega, rate)
     }
 
+    /// Like [`Oscillator::next`], but also gives the quadrature output `ŷ`, 90° behind, when the state has a wavetable for it.
+    ///
+    /// Together they're the analytic signal `y + iŷ`, whose magnitude is the envelope, from a single evaluation.
+    pub fn next_analytic(&mut self, rate: F) -> (F, Option<F>)
+    where
+        F: Euclid
+    {
+        let tau = F::TAU();
+
+        self.theta = (self.theta + self.omega/rate).rem_euclid(&tau);
+        self.state.next_analytic(self.theta + self.phi, self.omega, rate)
+    }
+
     pub fn map_state<SS>(self, map: impl FnOnce(S) -> SS) -> Oscillator<F, SS>
     where
         SS: OscillatorState<F>
@@ -187,14 +233,82 @@ where
     }
 }
 
+/// Object-safe view of an [`Oscillator`], so oscillators of different states and waveforms can be used behind `dyn`.
+pub trait DynOscillator<F>
+{
+    fn next(&mut self, rate: F) -> F;
+
+    fn omega(&self) -> F;
+    fn set_omega(&mut self, omega: F);
+
+    fn phi(&self) -> F;
+    fn set_phi(&mut self, phi: F);
+
+    fn theta(&self) -> F;
+    fn set_theta(&mut self, theta: F);
+
+    fn duty_cycle(&self) -> F;
+    fn set_duty_cycle(&mut self, duty_cycle: F);
+}
+
+impl<F, S> DynOscillator<F> for Oscillator<F, S>
+where
+    F: Float + FloatConst + Euclid,
+    S: OscillatorState<F>
+{
+    fn next(&mut self, rate: F) -> F
+    {
+        Oscillator::next(self, rate)
+    }
+
+    fn omega(&self) -> F
+    {
+        self.omega
+    }
+    fn set_omega(&mut self, omega: F)
+    {
+        self.omega = omega
+    }
+
+    fn phi(&self) -> F
+    {
+        self.phi
+    }
+    fn set_phi(&mut self, phi: F)
+    {
+        self.phi = phi
+    }
+
+    fn theta(&self) -> F
+    {
+        Oscillator::theta(self)
+    }
+    fn set_theta(&mut self, theta: F)
+    {
+        Oscillator::set_theta(self, theta)
+    }
+
+    fn duty_cycle(&self) -> F
+    {
+        self.state.duty_cycle()
+    }
+    fn set_duty_cycle(&mut self, duty_cycle: F)
+    {
+        self.state.set_duty_cycle(duty_cycle)
+    }
+}
+
 #[cfg(test)]
 mod test
 {
     use core::{error::Error, f32::consts::TAU};
 
-    use crate::waveform::Sawtooth;
+    use crate::{
+        waveform::{DynWaveform, Frames, Sawtooth, Sine, Square, Triangle, Vector, VectorBlend, Waveform},
+        Wavetable
+    };
 
-    use super::{Oscillator, Wave};
+    use super::{Direct, DynOscillator, Oscillator, OscillatorState, Scan, VectorWave, Wave};
 
     #[test]
     fn it_works() -> Result<(), Box<dyn Error>>
@@ -211,4 +325,79 @@ mod test
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn dyn_oscillator()
+    {
+        const RATE: f32 = 8000.0;
+
+        let triangle: &dyn DynWaveform<f32> = &Triangle;
+
+        let mut osc1 = Oscillator::new(TAU*100.0, 0.0, Direct::from(Sine));
+        let mut osc2 = Oscillator::new(TAU*200.0, 0.0, Wave::<_, _, 64>::from(Square)).with_dtc(0.5);
+        let mut osc3 = Oscillator::new(TAU*300.0, 0.0, Direct::from(triangle));
+        let oscs: [&mut dyn DynOscillator<f32>; 3] = [&mut osc1, &mut osc2, &mut osc3];
+
+        for osc in oscs
+        {
+            osc.set_duty_cycle(0.25);
+            osc.set_omega(osc.omega()*2.0);
+            assert!(osc.next(RATE).is_finite());
+        }
+        assert_eq!(osc1.duty_cycle(), 0.5);
+        assert_eq!(osc2.duty_cycle(), 0.25);
+        assert_eq!(osc3.omega, TAU*600.0);
+
+        // Band-limited behind dyn, just like the concrete waveform.
+        let sawtooth: &dyn DynWaveform<f32> = &Sawtooth;
+        let mut osc1 = Oscillator::new(TAU*300.0, 0.0, Wave::<_, _, 64>::from(sawtooth));
+        let mut osc2 = Oscillator::new(TAU*300.0, 0.0, Wave::<_, _, 64>::from(Sawtooth));
+        for _ in 0..100
+        {
+            assert!((osc1.next(RATE) - osc2.next(RATE)).abs() < 1e-5);
+        }
+        assert!(osc1.wavetable().is_some());
+    }
+
+    #[test]
+    fn analytic()
+    {
+        const RATE: f32 = 8000.0;
+
+        let mut sine = Oscillator::new(TAU*100.0, 0.0, Wave::<_, _, 64>::from(Sine));
+        let mut direct = Oscillator::new(TAU*100.0, 0.0, Direct::from(Sine));
+        for _ in 0..100
+        {
+            let (y, y_hat) = sine.next_analytic(RATE);
+            let y_hat = y_hat.unwrap();
+            assert!((y - sine.theta().cos()).abs() < 1e-4);
+            assert!((y_hat - sine.theta().sin()).abs() < 1e-4);
+            assert!((y*y + y_hat*y_hat - 1.0).abs() < 1e-4);
+
+            assert_eq!(direct.next_analytic(RATE), (direct.theta().cos(), None));
+        }
+
+        // Low enough for `next` to fall back on the naive waveform, so ŷ comes from the first `N` harmonics.
+        let omega = TAU*20.0;
+        let saw: Wavetable<f32, 16> = Sawtooth.wavetable().unwrap();
+        let hilbert = saw.hilbert();
+        let mut wave = Oscillator::new(omega, 0.0, Wave::<_, _, 16>::from(Sawtooth));
+        let mut wave_dtc = wave.with_dtc(0.25);
+        let mut scan = Oscillator::new(omega, 0.0, Scan::from(Frames::new([saw; 2], 0.5)));
+        let mut vector = Oscillator::new(omega, 0.0, VectorWave::<_, _, 16>::from(Vector::new([Sawtooth; 4], VectorBlend::Spectral)));
+        for _ in 0..100
+        {
+            let (y, y_hat) = wave.next_analytic(RATE);
+            let theta = wave.theta();
+            assert!((y - saw.waveform(theta, 16).unwrap()).abs() < 1e-4);
+            assert!((y_hat.unwrap() - hilbert.waveform(theta, 16).unwrap()).abs() < 1e-4);
+
+            for (y_k, y_hat_k) in [scan.next_analytic(RATE), vector.next_analytic(RATE)]
+            {
+                assert!((y_k - y).abs() < 1e-4);
+                assert!((y_hat_k.unwrap() - y_hat.unwrap()).abs() < 1e-4);
+            }
+            assert!(wave_dtc.next_analytic(RATE).1.is_some());
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use num_traits::{Euclid, Float, FloatConst};
+
+use super::{Oscillator, OscillatorState};
+
+/// How a [`Modulated`] combines its carrier and modulator.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+pub enum Modulation<F>
+{
+    /// `carrier·modulator`
+    #[default]
+    Ring,
+    /// `carrier·(1 + depth·modulator)`
+    Amplitude(F)
+}
+
+/// Ring or amplitude modulation of two oscillators, with the modulator at any `ratio` times the frequency of the carrier.
+///
+/// For integer ratios, the [`RingMod`](crate::waveform::RingMod) and [`Am`](crate::waveform::Am) waveforms have exact wavetables.
+/// Here, each oscillator is band-limited on its own, by its state, so their sum tones can still alias if both are bright.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Modulated<F, A, B>
+where
+    F: Float + FloatConst,
+    A: OscillatorState<F>,
+    B: OscillatorState<F>
+{
+    pub carrier: Oscillator<F, A>,
+    pub modulator: Oscillator<F, B>,
+    pub ratio: F,
+    pub modulation: Modulation<F>
+}
+
+impl<F, A, B> Modulated<F, A, B>
+where
+    F: Float + FloatConst + Euclid,
+    A: OscillatorState<F>,
+    B: OscillatorState<F>
+{
+    pub fn new(carrier: Oscillator<F, A>, modulator: Oscillator<F, B>, ratio: F, modulation: Modulation<F>) -> Self
+    {
+        Self {
+            carrier,
+            modulator,
+            ratio,
+            modulation
+        }
+    }
+
+    pub fn next(&mut self, rate: F) -> F
+    {
+        self.modulator.omega = self.carrier.omega*self.ratio;
+        let carrier = self.carrier.next(rate);
+        let modulator = self.modulator.next(rate);
+        match self.modulation
+        {
+            Modulation::Ring => carrier*modulator,
+            Modulation::Amplitude(depth) => carrier*(F::one() + depth*modulator)
+        }
+    }
+
+    /// Fills `block` with the output.
+    pub fn process(&mut self, rate: F, block: &mut [F])
+    {
+        for y in block.iter_mut()
+        {
+            *y = self.next(rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f64::consts::{PI, TAU};
+
+    use crate::{
+        oscillator::{Oscillator, Wave},
+        waveform::{Sawtooth, Sine}
+    };
+
+    use super::{Modulated, Modulation};
+
+    const RATE: f64 = 48000.0;
+
+    #[test]
+    fn it_works()
+    {
+        let omega = TAU*440.0;
+        let carrier = Oscillator::new(omega, 0.0, Wave::<_, _, 64>::from(Sine));
+        let modulator = Oscillator::new(0.0, 0.0, Wave::<_, _, 64>::from(Sine));
+
+        let mut ring = Modulated::new(carrier, modulator, 1.414, Modulation::Ring);
+        let mut am = Modulated::new(carrier, modulator, 1.414, Modulation::Amplitude(0.5));
+        for k in 1..256
+        {
+            let theta = k as f64*omega/RATE;
+            let y = theta.cos()*(1.414*theta).cos();
+            assert!((ring.next(RATE) - y).abs() < 1e-6);
+            assert!((am.next(RATE) - (theta.cos() + 0.5*y)).abs() < 1e-6);
+        }
+
+        // Both band-limited near Nyquist.
+        let mut bright = Modulated::new(
+            Oscillator::new(TAU*15000.0, 0.0, Wave::<_, _, 64>::from(Sawtooth)),
+            Oscillator::new(0.0, 0.0, Wave::<_, _, 64>::from(Sawtooth)),
+            PI/2.0,
+            Modulation::Ring
+        );
+        let mut block = [0.0; 64];
+        bright.process(RATE, &mut block);
+        assert!(block.iter().all(|y| y.is_finite() && y.abs() <= 1.0));
+    }
+}
@@ -0,0 +1,195 @@
+use num_traits::{Float, FloatConst};
+
+use crate::waveform::{Frames, Waveform};
+
+use super::{Direct, OscillatorState, Wave, WaveDTC};
+
+/// A band-limited oscillator state that scans through `K` wavetable frames.
+///
+/// Unlike [`Wave`], there's no cached wavetable. The frames are band-limited as they are evaluated, so the position can be moved
+/// every sample.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+#[serde(bound(serialize = "F: serde::Serialize", deserialize = "F: serde::Deserialize<'de>"))]
+pub struct Scan<F, const K: usize, const N: usize>
+where
+    F: Float
+{
+    pub waveform: Frames<F, K, N>
+}
+
+impl<F, const K: usize, const N: usize> Scan<F, K, N>
+where
+    F: Float
+{
+    pub fn position(&self) -> F
+    {
+        self.waveform.position
+    }
+    pub fn set_position(&mut self, position: F)
+    {
+        self.waveform.position = position
+    }
+}
+
+impl<F, const K: usize, const N: usize> From<Frames<F, K, N>> for Scan<F, K, N>
+where
+    F: Float
+{
+    fn from(waveform: Frames<F, K, N>) -> Self
+    {
+        Self {
+            waveform
+        }
+    }
+}
+impl<F, const K: usize, const N: usize> From<Direct<Frames<F, K, N>>> for Scan<F, K, N>
+where
+    F: Float
+{
+    fn from(value: Direct<Frames<F, K, N>>) -> Self
+    {
+        value.waveform.into()
+    }
+}
+impl<F, const K: usize, const N: usize> From<Scan<F, K, N>> for WaveDTC<F, Frames<F, K, N>, N>
+where
+    F: Float + FloatConst
+{
+    fn from(value: Scan<F, K, N>) -> Self
+    {
+        value.waveform.into()
+    }
+}
+
+impl<F, const K: usize, const N: usize> OscillatorState<F> for Scan<F, K, N>
+where
+    F: Float + FloatConst
+{
+    type Waveform = Frames<F, K, N>;
+
+    type WithDTC = WaveDTC<F, Frames<F, K, N>, N>;
+    type WithoutDTC = Scan<F, K, N>;
+
+    type WithWavetable<const NN: usize> = Wave<F, Frames<F, K, N>, NN>;
+    type WithoutWavetable = Direct<Frames<F, K, N>>;
+
+    type WithWaveform<WW> = Wave<F, WW, N>
+    where
+        WW: Waveform<F>;
+
+    fn next(&mut self, theta: F, omega: F, rate: F) -> F
+    {
+        let zero = F::zero();
+        let nyq = F::PI()*rate;
+        if nyq <= omega
+        {
+            return zero
+        }
+
+        let up_to = (nyq/omega).abs().to_usize().unwrap_or(N).min(N);
+        self.waveform.waveform_up_to(theta, up_to)
+            .unwrap_or_else(|| self.waveform.waveform(theta))
+    }
+
+    fn next_analytic(&mut self, theta: F, omega: F, rate: F) -> (F, Option<F>)
+    {
+        let zero = F::zero();
+        let nyq = F::PI()*rate;
+        if nyq <= omega
+        {
+            return (zero, Some(zero))
+        }
+
+        let up_to = (nyq/omega).abs().to_usize().unwrap_or(N).min(N);
+        match self.waveform.analytic_up_to(theta, up_to)
+        {
+            Some(y) => (y.re, Some(y.im)),
+            None => (self.waveform.waveform(theta), None)
+        }
+    }
+
+    fn waveform(&self) -> &Self::Waveform
+    {
+        &self.waveform
+    }
+    fn waveform_mut(&mut self) -> &mut Self::Waveform
+    {
+        &mut self.waveform
+    }
+
+    fn with_dtc(self, duty_cycle: F) -> Self::WithDTC
+    {
+        self.without_wavetable().with_dtc(duty_cycle).with_wavetable()
+    }
+    fn without_dtc(self) -> Self::WithoutDTC
+    {
+        self
+    }
+
+    fn with_wavetable<const NN: usize>(self) -> Self::WithWavetable<NN>
+    {
+        self.waveform.into()
+    }
+    fn without_wavetable(self) -> Self::WithoutWavetable
+    {
+        self.waveform.into()
+    }
+
+    fn map_waveform<WW>(self, waveform: impl FnOnce(Self::Waveform) -> WW) -> Self::WithWaveform<WW>
+    where
+        WW: Waveform<F>
+    {
+        waveform(self.waveform).into()
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f64::consts::TAU;
+
+    use crate::{
+        oscillator::{Oscillator, Wave},
+        waveform::Frames,
+        Wavetable
+    };
+
+    use super::Scan;
+
+    #[test]
+    fn it_works()
+    {
+        const RATE: f64 = 8000.0;
+
+        let frames = Frames::new([
+            Wavetable::from_array(0.0, [(1.0, 0.0), (0.0, 0.0)]),
+            Wavetable::from_array(0.5, [(0.0, 0.0), (0.0, 1.0)]),
+            Wavetable::from_array(0.0, [(0.0, -1.0), (0.0, 0.0)])
+        ], 0.0);
+
+        for position in [0.0, 0.25, 1.0, 1.5, 2.0]
+        {
+            let mut frames = frames;
+            frames.position = position;
+
+            let mut scan = Oscillator::new(TAU*100.0, 0.0, Scan::from(frames));
+            let mut wave = Oscillator::new(TAU*100.0, 0.0, Wave::<_, _, 2>::from(frames));
+            for _ in 0..100
+            {
+                assert!((scan.next(RATE) - wave.next(RATE)).abs() < 1e-9);
+            }
+        }
+
+        // Above a third of Nyquist, only the first harmonic is kept.
+        let mut scan = Oscillator::new(TAU*3000.0, 0.0, Scan::from(frames));
+        scan.set_position(1.0);
+        for _ in 0..100
+        {
+            let y = scan.next(RATE);
+            assert!((y - 0.5).abs() < 1e-9);
+        }
+
+        let json = serde_json::to_string(&scan).unwrap();
+        assert_eq!(serde_json::from_str::<Oscillator<f64, Scan<f64, 3, 2>>>(&json).unwrap(), scan);
+    }
+}
@@ -0,0 +1,391 @@
+use num_traits::{Float, FloatConst};
+
+use crate::{waveform::Waveform, WavetableView};
+
+use super::{Direct, DirectDTC, Oscillator, OscillatorState, Wave, WaveDTC};
+
+/// An oscillator state that can switch between [`Direct`], [`DirectDTC`], [`Wave`] and [`WaveDTC`] at runtime.
+///
+/// Unlike [`OscillatorState::with_dtc`] and [`OscillatorState::with_wavetable`], switching doesn't change the type, so it can be
+/// done on a stored oscillator without losing its phase.
+///
+/// [`OscillatorState::without_wavetable`] has to give one type for every variant, so it always gives a [`DirectDTC`], and a state
+/// without a duty cycle picks up the default one. Use [`set_band_limited`](Self::set_band_limited) to keep it a `Switch`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+#[serde(bound(deserialize = "Direct<W>: serde::Deserialize<'de>, DirectDTC<F, W>: serde::Deserialize<'de>, Wave<F, W, N>: serde::Deserialize<'de>, \
+    WaveDTC<F, W, N>: serde::Deserialize<'de>"))]
+pub enum Switch<F, W, const N: usize>
+where
+    F: Float + FloatConst,
+    W: Waveform<F>
+{
+    Direct(Direct<W>),
+    DirectDTC(DirectDTC<F, W>),
+    Wave(Wave<F, W, N>),
+    WaveDTC(WaveDTC<F, W, N>)
+}
+
+impl<F, W, const N: usize> Switch<F, W, N>
+where
+    F: Float + FloatConst,
+    W: Waveform<F>
+{
+    pub fn is_band_limited(&self) -> bool
+    {
+        matches!(self, Self::Wave(_) | Self::WaveDTC(_))
+    }
+
+    pub fn dtc(&self) -> Option<F>
+    {
+        match self
+        {
+            Self::DirectDTC(state) => Some(state.duty_cycle),
+            Self::WaveDTC(state) => Some(state.duty_cycle),
+            Self::Direct(_) | Self::Wave(_) => None
+        }
+    }
+
+    /// Turns band-limiting through a wavetable on or off. Does nothing, and keeps the cached wavetable, if it's already in that mode.
+    ///
+    /// The new state is built from a clone of the waveform, since it can't be moved out from behind a reference.
+    pub fn set_band_limited(&mut self, band_limited: bool)
+    where
+        W: Clone
+    {
+        if self.is_band_limited() == band_limited
+        {
+            return
+        }
+        let waveform = self.waveform().clone();
+        *self = match self.dtc()
+        {
+            Some(duty_cycle) => {
+                let state = DirectDTC {waveform, duty_cycle};
+                if band_limited {Self::WaveDTC(state.with_wavetable())} else {Self::DirectDTC(state)}
+            },
+            None => {
+                let state = Direct::from(waveform);
+                if band_limited {Self::Wave(state.with_wavetable())} else {Self::Direct(state)}
+            }
+        }
+    }
+
+    /// Turns the duty cycle on or off, or changes it. The cached wavetable is kept if the duty cycle stays the same.
+    ///
+    /// Turning it on or off builds the new state from a clone of the waveform, like [`set_band_limited`](Self::set_band_limited).
+    pub fn set_dtc(&mut self, duty_cycle: Option<F>)
+    where
+        W: Clone
+    {
+        match duty_cycle
+        {
+            Some(duty_cycle) if self.dtc().is_some() => self.set_duty_cycle(duty_cycle),
+            None if self.dtc().is_none() => (),
+            _ => {
+                let waveform = self.waveform().clone();
+                let state = match duty_cycle
+                {
+                    Some(duty_cycle) => Self::DirectDTC(DirectDTC {waveform, duty_cycle}),
+                    None => Self::Direct(Direct::from(waveform))
+                };
+                *self = if self.is_band_limited() {state.with_wavetable()} else {state}
+            }
+        }
+    }
+}
+
+impl<F, W, const N: usize> Default for Switch<F, W, N>
+where
+    F: Float + FloatConst,
+    W: Waveform<F> + Default
+{
+    fn default() -> Self
+    {
+        Self::Direct(Direct::default())
+    }
+}
+
+impl<F, W, const N: usize> From<W> for Switch<F, W, N>
+where
+    F: Float + FloatConst,
+    W: Waveform<F>
+{
+    fn from(waveform: W) -> Self
+    {
+        Direct::from(waveform).into()
+    }
+}
+impl<F, W, const N: usize> From<Direct<W>> for Switch<F, W, N>
+where
+    F: Float + FloatConst,
+    W: Waveform<F>
+{
+    fn from(value: Direct<W>) -> Self
+    {
+        Self::Direct(value)
+    }
+}
+impl<F, W, const N: usize> From<DirectDTC<F, W>> for Switch<F, W, N>
+where
+    F: Float + FloatConst,
+    W: Waveform<F>
+{
+    fn from(value: DirectDTC<F, W>) -> Self
+    {
+        Self::DirectDTC(value)
+    }
+}
+impl<F, W, const N: usize> From<Wave<F, W, N>> for Switch<F, W, N>
+where
+    F: Float + FloatConst,
+    W: Waveform<F>
+{
+    fn from(value: Wave<F, W, N>) -> Self
+    {
+        Self::Wave(value)
+    }
+}
+impl<F, W, const N: usize> From<WaveDTC<F, W, N>> for Switch<F, W, N>
+where
+    F: Float + FloatConst,
+    W: Waveform<F>
+{
+    fn from(value: WaveDTC<F, W, N>) -> Self
+    {
+        Self::WaveDTC(value)
+    }
+}
+
+impl<F, W, const N: usize> OscillatorState<F> for Switch<F, W, N>
+where
+    F: Float + FloatConst,
+    W: Waveform<F>
+{
+    type Waveform = W;
+
+    type WithDTC = Switch<F, W, N>;
+    type WithoutDTC = Switch<F, W, N>;
+
+    type WithWavetable<const NN: usize> = Switch<F, W, NN>;
+    type WithoutWavetable = DirectDTC<F, W>;
+
+    type WithWaveform<WW> = Switch<F, WW, N>
+    where
+        WW: Waveform<F>;
+
+    fn delete_cache(&mut self)
+    {
+        match self
+        {
+            Self::Direct(state) => state.delete_cache(),
+            Self::DirectDTC(state) => state.delete_cache(),
+            Self::Wave(state) => state.delete_cache(),
+            Self::WaveDTC(state) => state.delete_cache()
+        }
+    }
+
+    fn next(&mut self, theta: F, omega: F, rate: F) -> F
+    {
+        match self
+        {
+            Self::Direct(state) => state.next(theta, omega, rate),
+            Self::DirectDTC(state) => state.next(theta, omega, rate),
+            Self::Wave(state) => state.next(theta, omega, rate),
+            Self::WaveDTC(state) => state.next(theta, omega, rate)
+        }
+    }
+
+    fn next_analytic(&mut self, theta: F, omega: F, rate: F) -> (F, Option<F>)
+    {
+        match self
+        {
+            Self::Direct(state) => state.next_analytic(theta, omega, rate),
+            Self::DirectDTC(state) => state.next_analytic(theta, omega, rate),
+            Self::Wave(state) => state.next_analytic(theta, omega, rate),
+            Self::WaveDTC(state) => state.next_analytic(theta, omega, rate)
+        }
+    }
+
+    fn duty_cycle(&self) -> F
+    {
+        match self
+        {
+            Self::Direct(state) => state.duty_cycle(),
+            Self::DirectDTC(state) => state.duty_cycle(),
+            Self::Wave(state) => state.duty_cycle(),
+            Self::WaveDTC(state) => state.duty_cycle()
+        }
+    }
+    fn set_duty_cycle(&mut self, duty_cycle: F)
+    {
+        match self
+        {
+            Self::Direct(state) => state.set_duty_cycle(duty_cycle),
+            Self::DirectDTC(state) => state.set_duty_cycle(duty_cycle),
+            Self::Wave(state) => state.set_duty_cycle(duty_cycle),
+            Self::WaveDTC(state) => state.set_duty_cycle(duty_cycle)
+        }
+    }
+
+    fn wavetable(&self) -> Option<WavetableView<'_, F>>
+    {
+        match self
+        {
+            Self::Direct(state) => state.wavetable(),
+            Self::DirectDTC(state) => state.wavetable(),
+            Self::Wave(state) => state.wavetable(),
+            Self::WaveDTC(state) => state.wavetable()
+        }
+    }
+
+    fn waveform(&self) -> &Self::Waveform
+    {
+        match self
+        {
+            Self::Direct(state) => state.waveform(),
+            Self::DirectDTC(state) => state.waveform(),
+            Self::Wave(state) => state.waveform(),
+            Self::WaveDTC(state) => state.waveform()
+        }
+    }
+    fn waveform_mut(&mut self) -> &mut Self::Waveform
+    {
+        match self
+        {
+            Self::Direct(state) => state.waveform_mut(),
+            Self::DirectDTC(state) => state.waveform_mut(),
+            Self::Wave(state) => state.waveform_mut(),
+            Self::WaveDTC(state) => state.waveform_mut()
+        }
+    }
+
+    fn with_dtc(self, duty_cycle: F) -> Self::WithDTC
+    {
+        match self
+        {
+            Self::Direct(state) => Self::DirectDTC(state.with_dtc(duty_cycle)),
+            Self::DirectDTC(state) => Self::DirectDTC(state.with_dtc(duty_cycle)),
+            Self::Wave(state) => Self::WaveDTC(state.with_dtc(duty_cycle)),
+            Self::WaveDTC(state) => Self::WaveDTC(state.with_dtc(duty_cycle))
+        }
+    }
+    fn without_dtc(self) -> Self::WithoutDTC
+    {
+        match self
+        {
+            Self::Direct(state) => Self::Direct(state),
+            Self::DirectDTC(state) => Self::Direct(state.without_dtc()),
+            Self::Wave(state) => Self::Wave(state),
+            Self::WaveDTC(state) => Self::Wave(state.without_dtc())
+        }
+    }
+
+    fn with_wavetable<const NN: usize>(self) -> Self::WithWavetable<NN>
+    {
+        match self
+        {
+            Self::Direct(state) => Switch::Wave(state.with_wavetable()),
+            Self::DirectDTC(state) => Switch::WaveDTC(state.with_wavetable()),
+            Self::Wave(state) => Switch::Wave(state.with_wavetable()),
+            Self::WaveDTC(state) => Switch::WaveDTC(state.with_wavetable())
+        }
+    }
+    /// Since the type can't tell whether there's a duty cycle, this falls back on the default duty cycle if there's none.
+    fn without_wavetable(self) -> Self::WithoutWavetable
+    {
+        match self
+        {
+            Self::Direct(state) => state.into(),
+            Self::DirectDTC(state) => state,
+            Self::Wave(state) => state.without_wavetable().into(),
+            Self::WaveDTC(state) => state.without_wavetable()
+        }
+    }
+
+    fn map_waveform<WW>(self, waveform: impl FnOnce(Self::Waveform) -> WW) -> Self::WithWaveform<WW>
+    where
+        WW: Waveform<F>
+    {
+        match self
+        {
+            Self::Direct(state) => Switch::Direct(state.map_waveform(waveform)),
+            Self::DirectDTC(state) => Switch::DirectDTC(state.map_waveform(waveform)),
+            Self::Wave(state) => Switch::Wave(state.map_waveform(waveform)),
+            Self::WaveDTC(state) => Switch::WaveDTC(state.map_waveform(waveform))
+        }
+    }
+}
+
+impl<F, W, const N: usize> Oscillator<F, Switch<F, W, N>>
+where
+    F: Float + FloatConst,
+    W: Waveform<F>
+{
+    /// See [`Switch::set_band_limited`]. Unlike going through [`DerefMut`](core::ops::DerefMut), this keeps the cached wavetable when
+    /// possible.
+    pub fn set_band_limited(&mut self, band_limited: bool)
+    where
+        W: Clone
+    {
+        self.state.set_band_limited(band_limited)
+    }
+
+    /// See [`Switch::set_dtc`]. Unlike going through [`DerefMut`](core::ops::DerefMut), this keeps the cached wavetable when possible.
+    pub fn set_dtc(&mut self, duty_cycle: Option<F>)
+    where
+        W: Clone
+    {
+        self.state.set_dtc(duty_cycle)
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f32::consts::TAU;
+
+    use crate::{
+        oscillator::{Oscillator, OscillatorState},
+        waveform::Sawtooth
+    };
+
+    use super::Switch;
+
+    #[test]
+    fn it_works()
+    {
+        const RATE: f32 = 8000.0;
+
+        fn next(osc: &mut Oscillator<f32, Switch<f32, Sawtooth, 64>>) -> f32
+        {
+            assert!(osc.next(RATE).is_finite());
+            osc.theta()
+        }
+
+        let mut osc = Oscillator::new(TAU*100.0, 0.0, Switch::from(Sawtooth));
+
+        let theta = next(&mut osc);
+        osc.set_band_limited(true);
+        assert_eq!(osc.theta(), theta);
+        next(&mut osc);
+        assert!(osc.wavetable().is_some());
+        osc.set_band_limited(true);
+        assert!(osc.wavetable().is_some());
+
+        osc.set_dtc(Some(0.25));
+        assert_eq!(osc.dtc(), Some(0.25));
+        next(&mut osc);
+        osc.set_dtc(Some(0.25));
+        assert!(osc.wavetable().is_some());
+        osc.set_dtc(Some(0.3));
+        assert!(osc.wavetable().is_none());
+
+        let theta = next(&mut osc);
+        osc.set_band_limited(false);
+        osc.set_dtc(None);
+        assert_eq!(osc.theta(), theta);
+        assert!(!osc.is_band_limited());
+        assert_eq!(osc.dtc(), None);
+        next(&mut osc);
+    }
+}
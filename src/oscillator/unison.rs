@@ -0,0 +1,307 @@
+use core::ops::{Deref, DerefMut};
+
+use num_traits::{Euclid, Float, FloatConst};
+use rand::Rng;
+
+use super::OscillatorState;
+
+/// Relative detune of the outermost voice, when fully detuned.
+const DETUNE_MAX: f64 = 0.11002313;
+
+/// Relative voice detunes measured on the Roland JP-8000 super saw, by Adam Szabo.
+const SUPERSAW_OFFSETS: [f64; 7] = [-0.11002313, -0.06288439, -0.01952356, 0.0, 0.01991221, 0.06216538, 0.10745242];
+/// Polynomial fit of the JP-8000 detune knob response, by Adam Szabo. Highest degree first.
+const SUPERSAW_CURVE: [f64; 12] = [
+    10028.7312891634,
+    -50818.8652045924,
+    111363.4808729368,
+    -138150.6761080548,
+    106649.6679158292,
+    -53046.9642751875,
+    17019.9518580080,
+    -3425.0836591318,
+    404.2703938388,
+    -24.1878824391,
+    0.6717417634,
+    0.0030115596
+];
+/// Polynomial fits of the JP-8000 center and side voice levels against the mix knob, by Adam Szabo. Highest degree first.
+const SUPERSAW_CENTER: [f64; 2] = [-0.55366, 0.99785];
+const SUPERSAW_SIDE: [f64; 3] = [-0.73764, 1.2841, 0.044372];
+
+/// How the voices of a [`Unison`] are spread out in frequency.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+pub enum Detune
+{
+    /// Voices evenly spread, with the detune amount used as-is.
+    #[default]
+    Linear,
+    /// Voices spread like the JP-8000 super saw, with its non-linear detune knob.
+    Supersaw
+}
+
+impl Detune
+{
+    /// Relative frequency offset of voice `k` out of `voices`, given a detune amount in `[0, 1]`.
+    pub fn offset<F>(self, k: usize, voices: usize, amount: F) -> F
+    where
+        F: Float
+    {
+        let zero = F::zero();
+        let one = F::one();
+
+        if voices < 2
+        {
+            return zero
+        }
+        let amount = amount.clamp(zero, one);
+        let u = F::from(k).unwrap()/F::from(voices - 1).unwrap();
+
+        match self
+        {
+            Self::Linear => (u + u - one)*F::from(DETUNE_MAX).unwrap()*amount,
+            Self::Supersaw => {
+                let x = u*F::from(SUPERSAW_OFFSETS.len() - 1).unwrap();
+                let i = x.floor().to_usize().unwrap().min(SUPERSAW_OFFSETS.len() - 2);
+                let t = x - F::from(i).unwrap();
+                let a = F::from(SUPERSAW_OFFSETS[i]).unwrap();
+                let b = F::from(SUPERSAW_OFFSETS[i + 1]).unwrap();
+                let curve = SUPERSAW_CURVE.into_iter()
+                    .fold(zero, |y, c| y*amount + F::from(c).unwrap());
+                (a + (b - a)*t)*curve
+            }
+        }
+    }
+}
+
+/// `K` detuned copies of one oscillator state, panned across the stereo field.
+///
+/// All voices run through the same state, so a [`Wave`](super::Wave) only caches one [`Wavetable`](crate::Wavetable). The
+/// center voice and the side voices are mixed by `mix`, and the total power is kept the same.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Unison<F, S, const K: usize>
+where
+    F: Float + FloatConst,
+    S: OscillatorState<F>
+{
+    pub omega: F,
+    pub phi: F,
+    /// Detune amount, in `[0, 1]`.
+    pub detune: F,
+    pub spread: Detune,
+    /// Level of the side voices against the center voice, in `[0, 1]`. With the super saw, the center voice fades down as the side
+    /// voices come up, like on the JP-8000. At `1`, linearly spread voices are all equally loud.
+    pub mix: F,
+    /// Stereo width, in `[0, 1]`.
+    pub stereo: F,
+    theta: [F; K],
+    state: S
+}
+
+impl<F, S, const K: usize> Unison<F, S, K>
+where
+    F: Float + FloatConst,
+    S: OscillatorState<F>
+{
+    pub fn new(omega: F, phi: F, mut state: S) -> Self
+    {
+        state.delete_cache();
+        Self {
+            omega,
+            phi,
+            detune: F::zero(),
+            spread: Detune::default(),
+            mix: F::one(),
+            stereo: F::zero(),
+            theta: [F::zero(); K],
+            state
+        }
+    }
+
+    pub fn theta(&self) -> [F; K]
+    {
+        self.theta
+    }
+
+    pub fn randomize_phases(&mut self, rng: &mut impl Rng)
+    {
+        let tau = F::TAU();
+        for theta in self.theta.iter_mut()
+        {
+            *theta = F::from(rng.random::<f64>()).unwrap()*tau;
+        }
+    }
+
+    /// Relative frequency offset of each voice.
+    pub fn offsets(&self) -> [F; K]
+    {
+        core::array::from_fn(|k| self.spread.offset(k, K, self.detune))
+    }
+
+    /// Gain of each voice. The center voice, if `K` is odd, gets its own level, and the squared gains add up to one.
+    pub fn gains(&self) -> [F; K]
+    {
+        let zero = F::zero();
+        let one = F::one();
+
+        let mix = self.mix.clamp(zero, one);
+        let curve = |c: &[f64]| c.iter().fold(zero, |y, &c| y*mix + F::from(c).unwrap());
+        let (center, side) = match self.spread
+        {
+            Detune::Linear => (one, mix),
+            Detune::Supersaw => (curve(&SUPERSAW_CENTER), curve(&SUPERSAW_SIDE))
+        };
+        let is_center = |k: usize| K % 2 == 1 && k == K/2;
+
+        let gains: [F; K] = core::array::from_fn(|k| if is_center(k) {center} else {side});
+        let power = gains.iter().fold(zero, |p, &g| p + g*g);
+        if power > zero
+        {
+            let norm = power.sqrt().recip();
+            gains.map(|g| g*norm)
+        }
+        else
+        {
+            gains
+        }
+    }
+
+    pub fn next(&mut self, rate: F) -> [F; 2]
+    where
+        F: Euclid
+    {
+        let zero = F::zero();
+        let one = F::one();
+        let tau = F::TAU();
+        let quarter_pi = F::FRAC_PI_4();
+
+        let offsets = self.offsets();
+        let width = offsets.into_iter()
+            .map(Float::abs)
+            .fold(zero, Float::max);
+        let gains = self.gains();
+
+        let mut y = [zero; 2];
+        for ((theta, offset), gain) in self.theta.iter_mut().zip(offsets).zip(gains)
+        {
+            let omega = self.omega*(one + offset);
+            *theta = (*theta + omega/rate).rem_euclid(&tau);
+            let y_k = gain*self.state.next(*theta + self.phi, omega, rate);
+
+            let pan = if width > zero {offset/width*self.stereo.clamp(zero, one)} else {zero};
+            let (r, l) = ((pan + one)*quarter_pi).sin_cos();
+            y[0] = y[0] + l*y_k;
+            y[1] = y[1] + r*y_k;
+        }
+        y
+    }
+
+    pub fn map_state<SS>(self, map: impl FnOnce(S) -> SS) -> Unison<F, SS, K>
+    where
+        SS: OscillatorState<F>
+    {
+        let Self { omega, phi, detune, spread, mix, stereo, theta, mut state } = self;
+        state.delete_cache();
+        Unison {
+            omega,
+            phi,
+            detune,
+            spread,
+            mix,
+            stereo,
+            theta,
+            state: map(state)
+        }
+    }
+}
+
+impl<F, S, const K: usize> Deref for Unison<F, S, K>
+where
+    F: Float + FloatConst,
+    S: OscillatorState<F>
+{
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target
+    {
+        &self.state
+    }
+}
+impl<F, S, const K: usize> DerefMut for Unison<F, S, K>
+where
+    F: Float + FloatConst,
+    S: OscillatorState<F>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target
+    {
+        self.state.delete_cache();
+        &mut self.state
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f32::consts::TAU;
+
+    use crate::{
+        oscillator::{OscillatorState, Wave},
+        waveform::Sawtooth
+    };
+
+    use super::{Detune, Unison};
+
+    #[test]
+    fn it_works()
+    {
+        const K: usize = 7;
+        const RATE: f32 = 44100.0;
+
+        let mut unison = Unison::<_, _, K>::new(TAU*220.0, 0.0, Wave::<_, _, 256>::from(Sawtooth));
+        unison.detune = 0.5;
+        unison.spread = Detune::Supersaw;
+        unison.randomize_phases(&mut rand::rng());
+
+        let offsets = unison.offsets();
+        assert_eq!(offsets[K/2], 0.0);
+        assert!(offsets.is_sorted());
+
+        for _ in 0..256
+        {
+            let [l, r] = unison.next(RATE);
+            assert!(l.is_finite() && r.is_finite());
+            assert!((l - r).abs() < 1e-6);
+        }
+        assert!(unison.wavetable().is_some());
+
+        unison.stereo = 1.0;
+        let (l, r) = (0..256).map(|_| unison.next(RATE))
+            .fold((0.0, 0.0), |(l, r), [y_l, y_r]| (l + y_l*y_l, r + y_r*y_r));
+        assert_ne!(l, r);
+    }
+
+    #[test]
+    fn mix()
+    {
+        const K: usize = 7;
+
+        let mut unison = Unison::<_, _, K>::new(TAU*220.0, 0.0, Wave::<_, _, 256>::from(Sawtooth));
+        let gains = unison.gains();
+        assert!(gains.iter().all(|&g| (g - gains[0]).abs() < 1e-6));
+
+        unison.mix = 0.0;
+        assert_eq!(unison.gains()[K/2], 1.0);
+        assert_eq!(unison.gains()[0], 0.0);
+
+        unison.spread = Detune::Supersaw;
+        let mut center = f32::INFINITY;
+        for mix in [0.0, 0.25, 0.5, 0.75, 1.0]
+        {
+            unison.mix = mix;
+            let gains = unison.gains();
+            assert!((gains.iter().map(|g| g*g).sum::<f32>() - 1.0).abs() < 1e-5);
+            assert!(gains[K/2] < center);
+            center = gains[K/2];
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use core::ops::Mul;
+
+use num_traits::{Euclid, Float, FloatConst, Zero};
+
+use crate::{
+    waveform::{Vector, VectorBlend, Waveform},
+    Wavetable, WavetableView
+};
+
+use super::{Direct, Oscillator, OscillatorState, Wave, WaveDTC};
+
+/// A band-limited oscillator state for [`Vector`] synthesis, that lets the position move every sample.
+///
+/// The wavetables of the corners are cached, so with [`VectorBlend::Linear`], moving the position only changes how the corners
+/// are mixed. With [`VectorBlend::Spectral`], the morphed wavetable is rebuilt from the cached ones when the position has moved.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+pub struct VectorWave<F, W, const N: usize>
+where
+    F: Float,
+    W: Waveform<F>
+{
+    pub waveform: Vector<F, W>,
+    #[serde(skip)]
+    corners: Option<[Option<Wavetable<F, N>>; 4]>,
+    #[serde(skip)]
+    morphed: Option<Morphed<F, N>>
+}
+
+/// The morphed wavetable, and the position it was morphed at.
+type Morphed<F, const N: usize> = ((F, F), Option<Wavetable<F, N>>);
+
+impl<F, W, const N: usize> From<Vector<F, W>> for VectorWave<F, W, N>
+where
+    F: Float,
+    W: Waveform<F>
+{
+    fn from(waveform: Vector<F, W>) -> Self
+    {
+        Self {
+            waveform,
+            corners: None,
+            morphed: None
+        }
+    }
+}
+impl<F, W, const N: usize> From<Direct<Vector<F, W>>> for VectorWave<F, W, N>
+where
+    F: Float,
+    W: Waveform<F>
+{
+    fn from(value: Direct<Vector<F, W>>) -> Self
+    {
+        value.waveform.into()
+    }
+}
+impl<F, W, const N: usize> From<Wave<F, Vector<F, W>, N>> for VectorWave<F, W, N>
+where
+    F: Float + FloatConst + Euclid,
+    W: Waveform<F>
+{
+    fn from(value: Wave<F, Vector<F, W>, N>) -> Self
+    {
+        value.waveform.into()
+    }
+}
+impl<F, W, const N: usize> From<VectorWave<F, W, N>> for WaveDTC<F, Vector<F, W>, N>
+where
+    F: Float + FloatConst + Euclid,
+    W: Waveform<F>
+{
+    fn from(value: VectorWave<F, W, N>) -> Self
+    {
+        value.waveform.into()
+    }
+}
+
+impl<F, W, const N: usize> VectorWave<F, W, N>
+where
+    F: Float + FloatConst + Euclid,
+    W: Waveform<F>
+{
+    /// Evaluates the cached wavetables of the corners, and blends them at the position.
+    fn blend<T>(&mut self, waveform: impl Fn(&Wavetable<F, N>) -> Option<T>) -> Option<T>
+    where
+        T: Zero + Mul<F, Output = T>
+    {
+        let zero = F::zero();
+        let vector = &self.waveform;
+        let corners = self.corners.get_or_insert_with(|| vector.corners.each_ref().map(W::wavetable));
+        match vector.blend
+        {
+            VectorBlend::Linear => vector.weights()
+                .into_iter()
+                .zip(corners.iter())
+                .filter(|&(w, _)| w != zero)
+                .try_fold(T::zero(), |y, (w, wavetable)| Some(y + waveform(wavetable.as_ref()?)?*w)),
+            VectorBlend::Spectral => {
+                let position = (vector.x, vector.y);
+                if self.morphed.as_ref().is_none_or(|&(at, _)| at != position)
+                {
+                    self.morphed = Some((position, vector.blend_wavetables(corners.each_ref().map(Option::as_ref))));
+                }
+                self.morphed.as_ref()
+                    .and_then(|(_, wavetable)| waveform(wavetable.as_ref()?))
+            }
+        }
+    }
+}
+
+impl<F, W, const N: usize> OscillatorState<F> for VectorWave<F, W, N>
+where
+    F: Float + FloatConst + Euclid,
+    W: Waveform<F>
+{
+    type Waveform = Vector<F, W>;
+
+    type WithDTC = WaveDTC<F, Vector<F, W>, N>;
+    type WithoutDTC = VectorWave<F, W, N>;
+
+    type WithWavetable<const NN: usize> = VectorWave<F, W, NN>;
+    type WithoutWavetable = Direct<Vector<F, W>>;
+
+    type WithWaveform<WW> = Wave<F, WW, N>
+    where
+        WW: Waveform<F>;
+
+    fn delete_cache(&mut self)
+    {
+        self.corners = None;
+        self.morphed = None
+    }
+
+    fn next(&mut self, theta: F, omega: F, rate: F) -> F
+    {
+        let zero = F::zero();
+        let nyq = F::PI()*rate;
+        if nyq <= omega
+        {
+            return zero
+        }
+
+        let up_to = (nyq/omega).abs().to_usize().unwrap_or(N);
+        if N == 0 || up_to > N
+        {
+            return self.waveform.waveform(theta)
+        }
+        self.blend(|w| w.waveform(theta, up_to))
+            .unwrap_or_else(|| self.waveform.waveform(theta))
+    }
+
+    /// At low pitches, the first `N` harmonics are used, like for [`Wave`], so `ŷ` doesn't drop out.
+    fn next_analytic(&mut self, theta: F, omega: F, rate: F) -> (F, Option<F>)
+    {
+        let zero = F::zero();
+        let nyq = F::PI()*rate;
+        if nyq <= omega
+        {
+            return (zero, Some(zero))
+        }
+
+        let up_to = (nyq/omega).abs().to_usize().unwrap_or(N).min(N);
+        match self.blend(|w| w.analytic(theta, up_to))
+        {
+            Some(y) => (y.re, Some(y.im)),
+            None => (self.waveform.waveform(theta), None)
+        }
+    }
+
+    fn wavetable(&self) -> Option<WavetableView<'_, F>>
+    {
+        self.morphed.as_ref().and_then(|(_, w)| w.as_ref().map(Wavetable::view))
+    }
+
+    fn waveform(&self) -> &Self::Waveform
+    {
+        &self.waveform
+    }
+    fn waveform_mut(&mut self) -> &mut Self::Waveform
+    {
+        &mut self.waveform
+    }
+
+    fn with_dtc(self, duty_cycle: F) -> Self::WithDTC
+    {
+        self.without_wavetable().with_dtc(duty_cycle).with_wavetable()
+    }
+    fn without_dtc(self) -> Self::WithoutDTC
+    {
+        self
+    }
+
+    fn with_wavetable<const NN: usize>(self) -> Self::WithWavetable<NN>
+    {
+        self.waveform.into()
+    }
+    fn without_wavetable(self) -> Self::WithoutWavetable
+    {
+        self.waveform.into()
+    }
+
+    fn map_waveform<WW>(self, waveform: impl FnOnce(Self::Waveform) -> WW) -> Self::WithWaveform<WW>
+    where
+        WW: Waveform<F>
+    {
+        waveform(self.waveform).into()
+    }
+}
+
+impl<F, W, const N: usize> Oscillator<F, VectorWave<F, W, N>>
+where
+    F: Float + FloatConst + Euclid,
+    W: Waveform<F>
+{
+    /// Moves the position of the [`Vector`]. Unlike going through [`DerefMut`](core::ops::DerefMut), this keeps the cached
+    /// wavetables of the corners.
+    pub fn set_position(&mut self, x: F, y: F)
+    {
+        self.state.waveform.x = x;
+        self.state.waveform.y = y;
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f64::consts::TAU;
+
+    use crate::{
+        oscillator::{Oscillator, OscillatorState, Wave},
+        waveform::{MekkaWaveform, Vector, VectorBlend, Waveform},
+        Wavetable
+    };
+
+    use super::VectorWave;
+
+    #[test]
+    fn it_works()
+    {
+        const RATE: f64 = 44100.0;
+
+        let corners = [MekkaWaveform::Sine, MekkaWaveform::Triangle, MekkaWaveform::Sawtooth, MekkaWaveform::Square];
+        for blend in [VectorBlend::Linear, VectorBlend::Spectral]
+        {
+            let mut vector = Oscillator::new(TAU*440.0, 0.0, VectorWave::<_, _, 64>::from(Vector::new(corners, blend)));
+            for k in 0..200
+            {
+                let (x, y) = ((k as f64*0.01).sin().abs(), (k as f64*0.02).cos().abs());
+                vector.set_position(x, y);
+                let theta = vector.theta();
+
+                let mut reference = Oscillator::new(TAU*440.0, 0.0, Wave::<_, _, 64>::from(Vector {x, y, ..*vector.waveform()}));
+                reference.set_theta(theta);
+                assert!((vector.next(RATE) - reference.next(RATE)).abs() < 1e-9);
+            }
+        }
+
+        let mut vector = Vector::new(corners, VectorBlend::Spectral);
+        (vector.x, vector.y) = (0.5, 0.5);
+        let [w00, w10, w01, w11]: [Wavetable<f64, 8>; 4] = corners.map(|w| w.wavetable().unwrap());
+        let morphed = w00.morph(&w10, 0.5).morph(&w01.morph(&w11, 0.5), 0.5);
+        assert_eq!(vector.wavetable::<8>(), Some(morphed));
+    }
+}
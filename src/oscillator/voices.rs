@@ -0,0 +1,379 @@
+use num_traits::{Euclid, Float, FloatConst};
+
+use super::{Oscillator, OscillatorState};
+
+/// How many held notes a monophonic [`Voices`] remembers, to fall back on when the last one is released.
+const HELD: usize = 16;
+const A4: u8 = 69;
+/// How long it takes a voice's level to fall by `1/e` once its output gets quieter, in seconds.
+const LEVEL_RELEASE: f64 = 0.05;
+const SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// How incoming notes are assigned to voices.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+pub enum VoiceMode
+{
+    /// Each note gets its own voice.
+    #[default]
+    Poly,
+    /// One voice, retriggered by every note.
+    Mono,
+    /// One voice, only retriggered when no other note is held.
+    Legato
+}
+
+/// Which voice is taken over when all voices are busy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+pub enum Stealing
+{
+    #[default]
+    Oldest,
+    /// Steal the voice with the lowest [`level`](Voice::level).
+    Quietest,
+    /// Retrigger a voice already playing the same note, otherwise steal the oldest.
+    SameNote
+}
+
+/// What happens to a voice's phase when it's triggered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+pub enum PhaseReset
+{
+    /// Start every note at phase zero.
+    #[default]
+    Always,
+    /// Let the voices run freely, also while silent.
+    Never,
+    /// Start every note at a random phase, from a generator kept in [`Voices`], so triggering never waits on the system's
+    /// entropy source.
+    Random
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Voice<F, S>
+where
+    F: Float + FloatConst,
+    S: OscillatorState<F>
+{
+    pub oscillator: Oscillator<F, S>,
+    pub gain: F,
+    note: Option<u8>,
+    level: F,
+    age: u64
+}
+
+impl<F, S> Voice<F, S>
+where
+    F: Float + FloatConst,
+    S: OscillatorState<F>
+{
+    pub fn note(&self) -> Option<u8>
+    {
+        self.note
+    }
+
+    /// Peak of the voice's recent output, held at the note-on velocity until the first sample.
+    pub fn level(&self) -> F
+    {
+        self.level
+    }
+}
+
+/// A fixed set of `M` oscillators, playing notes summed together.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Voices<F, S, const M: usize>
+where
+    F: Float + FloatConst,
+    S: OscillatorState<F>
+{
+    pub mode: VoiceMode,
+    pub stealing: Stealing,
+    pub reset: PhaseReset,
+    /// Frequency of A4, in Hz.
+    pub tuning: F,
+    voices: [Voice<F, S>; M],
+    held: [Option<(u8, F)>; HELD],
+    clock: u64,
+    rng: u64
+}
+
+impl<F, S, const M: usize> Voices<F, S, M>
+where
+    F: Float + FloatConst,
+    S: OscillatorState<F>
+{
+    pub fn new(state: S) -> Self
+    where
+        S: Clone
+    {
+        let zero = F::zero();
+        Self {
+            mode: VoiceMode::default(),
+            stealing: Stealing::default(),
+            reset: PhaseReset::default(),
+            tuning: F::from(440.0).unwrap(),
+            voices: core::array::from_fn(|_| Voice {
+                oscillator: Oscillator::new(zero, zero, state.clone()),
+                gain: zero,
+                note: None,
+                level: zero,
+                age: 0
+            }),
+            held: [None; HELD],
+            clock: 0,
+            rng: SEED
+        }
+    }
+
+    /// Reseeds the generator behind [`PhaseReset::Random`].
+    pub fn seed(&mut self, seed: u64)
+    {
+        self.rng = if seed == 0 {SEED} else {seed}
+    }
+
+    pub fn voices(&self) -> &[Voice<F, S>; M]
+    {
+        &self.voices
+    }
+    pub fn voices_mut(&mut self) -> &mut [Voice<F, S>; M]
+    {
+        &mut self.voices
+    }
+
+    /// Angular frequency of a MIDI note.
+    pub fn omega(&self, note: u8) -> F
+    {
+        let twelve = F::from(12.0).unwrap();
+        let semitones = F::from(note as i16 - A4 as i16).unwrap();
+        F::TAU()*self.tuning*(semitones/twelve).exp2()
+    }
+
+    pub fn note_on(&mut self, note: u8, velocity: F)
+    {
+        if M == 0
+        {
+            return
+        }
+        match self.mode
+        {
+            VoiceMode::Poly => {
+                let i = self.voices.iter()
+                    .position(|voice| self.stealing == Stealing::SameNote && voice.note == Some(note))
+                    .or_else(|| self.voices.iter().position(|voice| voice.note.is_none()))
+                    .unwrap_or_else(|| self.steal());
+                self.trigger(i, note, velocity, true)
+            },
+            VoiceMode::Mono | VoiceMode::Legato => {
+                self.release_held(note);
+                if self.held[HELD - 1].is_some()
+                {
+                    self.held.copy_within(1.., 0);
+                    self.held[HELD - 1] = None;
+                }
+                if let Some(slot) = self.held.iter_mut().find(|held| held.is_none())
+                {
+                    *slot = Some((note, velocity));
+                }
+                let retrigger = self.mode == VoiceMode::Mono || self.voices[0].note.is_none();
+                self.trigger(0, note, velocity, retrigger)
+            }
+        }
+    }
+
+    pub fn note_off(&mut self, note: u8)
+    {
+        match self.mode
+        {
+            VoiceMode::Poly => {
+                for voice in self.voices.iter_mut()
+                {
+                    if voice.note == Some(note)
+                    {
+                        voice.note = None
+                    }
+                }
+            },
+            VoiceMode::Mono | VoiceMode::Legato => {
+                self.release_held(note);
+                if M != 0 && self.voices[0].note == Some(note)
+                {
+                    match self.held.iter().rev().find_map(|&held| held)
+                    {
+                        Some((note, velocity)) => self.trigger(0, note, velocity, false),
+                        None => self.voices[0].note = None
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn all_notes_off(&mut self)
+    {
+        self.held = [None; HELD];
+        for voice in self.voices.iter_mut()
+        {
+            voice.note = None
+        }
+    }
+
+    pub fn next(&mut self, rate: F) -> F
+    where
+        F: Euclid
+    {
+        let free_running = self.reset == PhaseReset::Never;
+        let release = F::one() - (F::from(LEVEL_RELEASE).unwrap()*rate).recip();
+        self.voices.iter_mut()
+            .map(|voice| {
+                let y = if free_running || voice.note.is_some()
+                {
+                    let y = voice.oscillator.next(rate);
+                    if voice.note.is_some() {voice.gain*y} else {F::zero()}
+                }
+                else
+                {
+                    F::zero()
+                };
+                voice.level = y.abs().max(voice.level*release);
+                y
+            })
+            .fold(F::zero(), |a, b| a + b)
+    }
+
+    /// Fills `block` with the summed output of all voices.
+    pub fn process(&mut self, rate: F, block: &mut [F])
+    where
+        F: Euclid
+    {
+        for y in block.iter_mut()
+        {
+            *y = self.next(rate)
+        }
+    }
+
+    fn steal(&self) -> usize
+    {
+        let oldest = || self.voices.iter()
+            .enumerate()
+            .min_by_key(|(_, voice)| voice.age)
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        match self.stealing
+        {
+            Stealing::Oldest | Stealing::SameNote => oldest(),
+            Stealing::Quietest => self.voices.iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.level.partial_cmp(&b.level).unwrap_or(core::cmp::Ordering::Equal))
+                .map(|(i, _)| i)
+                .unwrap_or_else(oldest)
+        }
+    }
+
+    fn release_held(&mut self, note: u8)
+    {
+        if let Some(i) = self.held.iter().position(|held| held.is_some_and(|(n, _)| n == note))
+        {
+            self.held.copy_within(i + 1.., i);
+            self.held[HELD - 1] = None;
+        }
+    }
+
+    /// Uniform in `[0, 1)`, from an xorshift generator, so it's cheap and never blocks.
+    fn random(&mut self) -> F
+    {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        F::from((self.rng >> 11) as f64/(1u64 << 53) as f64).unwrap()
+    }
+
+    fn trigger(&mut self, i: usize, note: u8, velocity: F, retrigger: bool)
+    {
+        let omega = self.omega(note);
+        let theta = match self.reset
+        {
+            PhaseReset::Random if retrigger => Some(self.random()*F::TAU()),
+            PhaseReset::Always if retrigger => Some(F::zero()),
+            _ => None
+        };
+        let voice = &mut self.voices[i];
+        voice.oscillator.omega = omega;
+        voice.gain = velocity;
+        voice.note = Some(note);
+        voice.level = voice.level.max(velocity);
+        if retrigger
+        {
+            voice.age = self.clock;
+            self.clock += 1;
+        }
+        if let Some(theta) = theta
+        {
+            voice.oscillator.set_theta(theta)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use crate::{oscillator::Wave, waveform::Sawtooth};
+
+    use super::{PhaseReset, Stealing, VoiceMode, Voices};
+
+    #[test]
+    fn it_works()
+    {
+        const RATE: f32 = 44100.0;
+
+        let mut voices = Voices::<_, _, 2>::new(Wave::<_, _, 64>::from(Sawtooth));
+        voices.note_on(60, 1.0);
+        voices.note_on(64, 0.5);
+        voices.note_on(67, 0.8);
+        assert_eq!(voices.voices().map(|voice| voice.note()), [Some(67), Some(64)]);
+
+        voices.stealing = Stealing::Quietest;
+        voices.note_on(72, 1.0);
+        assert_eq!(voices.voices().map(|voice| voice.note()), [Some(67), Some(72)]);
+
+        let mut block = [0.0; 64];
+        voices.process(RATE, &mut block);
+        assert!(block.iter().all(|y| y.is_finite()));
+
+        voices.voices_mut()[1].gain = 0.1;
+        let mut block = [0.0; 4096];
+        voices.process(RATE, &mut block);
+        assert!(voices.voices()[1].level() < 0.2);
+        voices.note_on(76, 1.0);
+        assert_eq!(voices.voices().map(|voice| voice.note()), [Some(67), Some(76)]);
+
+        let mut block = [0.0; 64];
+
+        voices.all_notes_off();
+        voices.mode = VoiceMode::Legato;
+        voices.note_on(60, 1.0);
+        voices.process(RATE, &mut block);
+        let theta = voices.voices()[0].oscillator.theta();
+        voices.note_on(62, 1.0);
+        assert_eq!(voices.voices()[0].oscillator.theta(), theta);
+        voices.note_off(62);
+        assert_eq!(voices.voices()[0].note(), Some(60));
+        voices.note_off(60);
+        assert_eq!(voices.voices()[0].note(), None);
+    }
+
+    #[test]
+    fn random_phase()
+    {
+        let mut voices = Voices::<f32, _, 4>::new(Wave::<_, _, 64>::from(Sawtooth));
+        voices.reset = PhaseReset::Random;
+        voices.seed(1);
+        let mut other = voices;
+        for note in [60, 64, 67, 72]
+        {
+            voices.note_on(note, 1.0);
+            other.note_on(note, 1.0);
+        }
+        let theta = voices.voices().map(|voice| voice.oscillator.theta());
+        assert_eq!(theta, other.voices().map(|voice| voice.oscillator.theta()));
+        assert!(theta.iter().all(|theta| (0.0..core::f32::consts::TAU).contains(theta)));
+        assert!(theta.windows(2).all(|theta| theta[0] != theta[1]));
+    }
+}
@@ -88,6 +88,30 @@ where
         }
     }
 
+    fn next_analytic(&mut self, theta: F, omega: F, rate: F) -> (F, Option<F>)
+    {
+        let zero = F::zero();
+        let pi = F::PI();
+        let nyq = pi*rate;
+        if nyq <= omega
+        {
+            return (zero, Some(zero))
+        }
+
+        // Below the pitch where `next` falls back on the waveform, the first `N` harmonics are used, so `ŷ` doesn't drop out.
+        if N != 0
+            && let up_to = (nyq/omega).abs().to_usize().unwrap_or(N).min(N)
+            && let Some(wavetable) = self.wavetable.get_or_insert_with(|| self.waveform.wavetable())
+            && let Some(y) = wavetable.analytic(theta, up_to)
+        {
+            (y.re, Some(y.im))
+        }
+        else
+        {
+            (self.waveform.waveform(theta), None)
+        }
+    }
+
     fn duty_cycle(&self) -> F
     {
         crate::duty_cycle_default()
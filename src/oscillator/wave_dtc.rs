@@ -79,6 +79,11 @@ where
     where
         WW: Waveform<F>;
 
+    fn delete_cache(&mut self)
+    {
+        self.wavetable = None
+    }
+
     fn next(&mut self, theta: F, omega: F, rate: F) -> F
     {
         let zero = F::zero();
@@ -103,10 +108,42 @@ where
         }
     }
 
+    fn next_analytic(&mut self, theta: F, omega: F, rate: F) -> (F, Option<F>)
+    {
+        let zero = F::zero();
+        let pi = F::PI();
+        let nyq = pi*rate;
+        if nyq <= omega
+        {
+            return (zero, Some(zero))
+        }
+
+        // Below the pitch where `next` falls back on the waveform, the first `N` harmonics are used, so `ŷ` doesn't drop out.
+        if N != 0
+            && let up_to = (nyq/omega).abs().to_usize().unwrap_or(N).min(N)
+            && let Some(wavetable) = self.wavetable.get_or_insert_with(|| self.waveform.wavetable_with_dtc(self.duty_cycle))
+            && let Some(y) = wavetable.analytic(theta, up_to)
+        {
+            (y.re, Some(y.im))
+        }
+        else
+        {
+            (self.waveform.waveform_with_dtc(theta, self.duty_cycle), None)
+        }
+    }
+
     fn duty_cycle(&self) -> F
     {
         self.duty_cycle
     }
+    fn set_duty_cycle(&mut self, duty_cycle: F)
+    {
+        if self.duty_cycle != duty_cycle
+        {
+            self.duty_cycle = duty_cycle;
+            self.delete_cache()
+        }
+    }
 
     fn wavetable(&self) -> Option<WavetableView<'_, F>>
     {
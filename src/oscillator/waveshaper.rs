@@ -0,0 +1,423 @@
+use num_traits::{Euclid, Float, FloatConst};
+
+use crate::Wavetable;
+
+use super::{Oscillator, OscillatorState};
+
+
+/// The order of antiderivative anti-aliasing (ADAA). Higher orders alias less, but dull the highs more, and delay the output by
+/// another half sample.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+pub enum AdaaOrder
+{
+    #[default]
+    First,
+    Second
+}
+
+/// The shape of a [`Waveshaper`].
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(bound(serialize = "F: serde::Serialize", deserialize = "F: serde::Deserialize<'de>"))]
+pub enum Shaper<F, const K: usize>
+{
+    /// The Chebyshev series `Σ c[k]·T_k(x)`, which turns a full-scale cosine into `Σ c[k]·cos(kθ)`.
+    ///
+    /// When the oscillator plays a pure sine from a wavetable, the shaped harmonics are found exactly, and the ones above
+    /// Nyquist are left out.
+    Chebyshev(#[serde(with = "crate::util::serde_array")] [F; K]),
+    /// Soft saturation, with first order ADAA.
+    Tanh,
+    /// A triangle wavefolder, folding the input back every time it passes `±1`, like the Buchla 259.
+    Fold(AdaaOrder)
+}
+
+/// Waveshaping of the output of an oscillator.
+///
+/// The output is amplified by `drive` before it's shaped, which sets the fold amount for the wavefolder, and can be changed from
+/// one sample to the next.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Waveshaper<F, S, const K: usize = 8>
+where
+    F: Float + FloatConst,
+    S: OscillatorState<F>
+{
+    pub oscillator: Oscillator<F, S>,
+    pub shaper: Shaper<F, K>,
+    pub drive: F,
+    history: [F; 2]
+}
+
+impl<F, S, const K: usize> Waveshaper<F, S, K>
+where
+    F: Float + FloatConst + Euclid,
+    S: OscillatorState<F>
+{
+    pub fn new(oscillator: Oscillator<F, S>, shaper: Shaper<F, K>) -> Self
+    {
+        let zero = F::zero();
+        Self {
+            oscillator,
+            shaper,
+            drive: F::one(),
+            history: [zero; 2]
+        }
+    }
+
+    /// Forgets the previous inputs, which the anti-aliasing depends on.
+    pub fn reset(&mut self)
+    {
+        self.history = [F::zero(); 2]
+    }
+
+    pub fn next(&mut self, rate: F) -> F
+    {
+        let (y, y_hat) = self.oscillator.next_analytic(rate);
+        let x = self.drive*y;
+        let [x1, x2] = self.history;
+        self.history = [x, x1];
+
+        match self.shaper
+        {
+            Shaper::Chebyshev(c) => match y_hat
+            {
+                Some(y_hat) if self.is_sine() => {
+                    let up_to = (F::PI()*rate/self.oscillator.omega).abs().to_usize().unwrap_or(K);
+                    chebyshev_wavetable(c, self.drive*y.hypot(y_hat))
+                        .waveform(y_hat.atan2(y), up_to)
+                        .unwrap_or_else(|| chebyshev(c, x))
+                },
+                _ => chebyshev(c, x)
+            },
+            Shaper::Tanh => adaa1(x, x1, F::tanh, log_cosh),
+            Shaper::Fold(AdaaOrder::First) => adaa1(x, x1, fold, fold_1),
+            Shaper::Fold(AdaaOrder::Second) => adaa2(x, x1, x2, fold, fold_1, fold_2)
+        }
+    }
+
+    /// Fills `block` with the output.
+    pub fn process(&mut self, rate: F, block: &mut [F])
+    {
+        for y in block.iter_mut()
+        {
+            *y = self.next(rate)
+        }
+    }
+
+    /// Whether the oscillator plays a single harmonic from its wavetable.
+    fn is_sine(&self) -> bool
+    {
+        let zero = F::zero();
+        self.oscillator.wavetable()
+            .is_some_and(|wavetable| *wavetable.a0 == zero
+                && wavetable.ab.get(1..).is_some_and(|ab| ab.iter().all(|&(a, b)| a == zero && b == zero)))
+    }
+}
+
+/// Evaluates a Chebyshev series with Clenshaw's recurrence.
+fn chebyshev<F, const K: usize>(c: [F; K], x: F) -> F
+where
+    F: Float
+{
+    let zero = F::zero();
+    let (b1, b2) = c.iter()
+        .skip(1)
+        .rev()
+        .fold((zero, zero), |(b1, b2), &c| (c + (x + x)*b1 - b2, b1));
+    c.first().copied().unwrap_or(zero) + x*b1 - b2
+}
+
+/// The harmonics of `Σ c[k]·T_k(g·cos θ)`.
+fn chebyshev_wavetable<F, const K: usize>(c: [F; K], g: F) -> Wavetable<F, K>
+where
+    F: Float
+{
+    let zero = F::zero();
+    let one = F::one();
+    let half = F::from(0.5).unwrap();
+
+    // To powers of g·cos θ, with T_{k+1} = 2x·T_k - T_{k-1}.
+    let mut power = [zero; K];
+    let mut t = [zero; K];
+    let mut t_prev = [zero; K];
+    for (k, &c) in c.iter().enumerate()
+    {
+        let t_next: [F; K] = match k
+        {
+            0 => core::array::from_fn(|m| if m == 0 {one} else {zero}),
+            1 => core::array::from_fn(|m| if m == 1 {one} else {zero}),
+            _ => core::array::from_fn(|m| if m == 0 {zero} else {t[m - 1] + t[m - 1]} - t_prev[m])
+        };
+        (t_prev, t) = (t, t_next);
+        for (p, &t) in power.iter_mut().zip(t.iter())
+        {
+            *p = *p + c*t
+        }
+    }
+
+    // To harmonics, with cosᵐθ = 2⁻ᵐ·Σ binom(m, i)·cos((m - 2i)θ).
+    let mut cosines = [zero; K];
+    let mut g_m = one;
+    for (m, &p) in power.iter().enumerate()
+    {
+        let mut binom = g_m*p*half.powi(m as i32);
+        for i in 0..=m
+        {
+            let j = m.abs_diff(2*i);
+            cosines[j] = cosines[j] + binom;
+            binom = binom*F::from(m - i).unwrap()/F::from(i + 1).unwrap();
+        }
+        g_m = g_m*g;
+    }
+
+    let dc = cosines.first().copied().unwrap_or(zero);
+    Wavetable::from_fn(dc, |m| (cosines.get(m + 1).copied().unwrap_or(zero), zero))
+}
+
+fn log_cosh<F>(x: F) -> F
+where
+    F: Float + FloatConst
+{
+    let x = x.abs();
+    x + (-(x + x)).exp().ln_1p() - F::LN_2()
+}
+
+/// The triangle wavefolder, with slope `1` through zero.
+fn fold<F>(x: F) -> F
+where
+    F: Float + Euclid
+{
+    let (u, two) = fold_phase(x);
+    if u < two {u - F::one()} else {two + F::one() - u}
+}
+/// An antiderivative of [`fold`], which is periodic.
+fn fold_1<F>(x: F) -> F
+where
+    F: Float + Euclid
+{
+    let half = F::from(0.5).unwrap();
+    let (u, two) = fold_phase(x);
+    if u < two
+    {
+        u*u*half - u
+    }
+    else
+    {
+        let v = u - two;
+        v - v*v*half
+    }
+}
+/// An antiderivative of [`fold_1`], which is also periodic, since `fold_1` has no DC-offset.
+fn fold_2<F>(x: F) -> F
+where
+    F: Float + Euclid
+{
+    let half = F::from(0.5).unwrap();
+    let sixth = F::from(6.0).unwrap().recip();
+    let (u, two) = fold_phase(x);
+    if u < two
+    {
+        u*u*(u*sixth - half)
+    }
+    else
+    {
+        let v = u - two;
+        v*v*(half - v*sixth) - F::from(2.0/3.0).unwrap()
+    }
+}
+/// Where in the fold's period of `4` the input is, starting at the bottom.
+fn fold_phase<F>(x: F) -> (F, F)
+where
+    F: Float + Euclid
+{
+    let two = F::from(2.0).unwrap();
+    ((x + F::one()).rem_euclid(&(two + two)), two)
+}
+
+/// How close two inputs can be, relative to their size, before antiderivative anti-aliasing falls back on evaluating the shape
+/// directly. The divided differences lose about `ε/dxᵏ` to rounding for order `k`, and the fallback is off by about `dx²`, so the
+/// two meet at `ε^(1/(k + 2))`.
+fn adaa_tolerance<F>(order: i32, x: F) -> F
+where
+    F: Float
+{
+    F::epsilon().powf(F::from(order + 2).unwrap().recip())*x.abs().max(F::one())
+}
+
+fn adaa1<F>(x0: F, x1: F, f: impl Fn(F) -> F, f_1: impl Fn(F) -> F) -> F
+where
+    F: Float
+{
+    let dx = x0 - x1;
+    if dx.abs() < adaa_tolerance(1, x0.abs().max(x1.abs()))
+    {
+        f((x0 + x1)*F::from(0.5).unwrap())
+    }
+    else
+    {
+        (f_1(x0) - f_1(x1))/dx
+    }
+}
+fn adaa2<F>(x0: F, x1: F, x2: F, f: impl Fn(F) -> F, f_1: impl Fn(F) -> F, f_2: impl Fn(F) -> F) -> F
+where
+    F: Float
+{
+    let tolerance = adaa_tolerance(2, x0.abs().max(x1.abs()).max(x2.abs()));
+    let half = F::from(0.5).unwrap();
+    let two = F::one() + F::one();
+
+    let dx = x0 - x2;
+    if dx.abs() < tolerance
+    {
+        // Ill-conditioned, so take the limit as `x0` and `x2` meet at their mean.
+        let x = (x0 + x2)*half;
+        let delta = x - x1;
+        return if delta.abs() < tolerance
+        {
+            f((x + x1)*half)
+        }
+        else
+        {
+            two/delta*(f_1(x) + (f_2(x1) - f_2(x))/delta)
+        }
+    }
+    let d = |a: F, b: F| if (a - b).abs() < tolerance
+    {
+        f_1((a + b)*half)
+    }
+    else
+    {
+        (f_2(a) - f_2(b))/(a - b)
+    };
+    (d(x0, x1) - d(x1, x2))*two/dx
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f64::consts::TAU;
+
+    use num_complex::Complex;
+
+    use crate::{
+        fft::fft,
+        oscillator::{Direct, Oscillator, Wave},
+        waveform::Sine
+    };
+
+    use super::{chebyshev, fold, fold_1, fold_2, AdaaOrder, Shaper, Waveshaper};
+
+    const RATE: f64 = 48000.0;
+
+    #[test]
+    fn chebyshev_exact()
+    {
+        let c = [0.2, 0.0, 0.5, -0.3];
+        for drive in [1.0, 0.5]
+        {
+            let mut shaper = Waveshaper::new(Oscillator::new(TAU*440.0, 0.3, Wave::<_, _, 8>::from(Sine)), Shaper::Chebyshev(c));
+            shaper.drive = drive;
+            for _ in 0..64
+            {
+                let y = shaper.next(RATE);
+                let theta = shaper.oscillator.theta() + 0.3;
+                assert!((y - chebyshev(c, drive*theta.cos())).abs() < 1e-9);
+            }
+        }
+
+        // The third harmonic is above Nyquist, and the second isn't. Also when the sine's own wavetable has fewer harmonics than
+        // fit below Nyquist.
+        for frequency in [10000.0, 9000.0]
+        {
+            let mut shaper = Waveshaper::new(Oscillator::new(TAU*frequency, 0.0, Wave::<_, _, 1>::from(Sine)), Shaper::Chebyshev(c));
+            let mut aliased = 0.0f64;
+            for _ in 0..64
+            {
+                let y = shaper.next(RATE);
+                let theta = shaper.oscillator.theta();
+                assert!((y - (0.2 + 0.5*(2.0*theta).cos())).abs() < 1e-9);
+                aliased = aliased.max((y - chebyshev(c, theta.cos())).abs());
+            }
+            assert!(aliased > 0.1);
+        }
+    }
+
+    #[test]
+    fn antiderivatives()
+    {
+        let eps = 1e-6;
+        for k in 0..200
+        {
+            let x = -10.0 + k as f64*0.1 + 0.05;
+            assert!((fold(x) - (fold_1(x + eps) - fold_1(x - eps))/(2.0*eps)).abs() < 1e-6);
+            assert!((fold_1(x) - (fold_2(x + eps) - fold_2(x - eps))/(2.0*eps)).abs() < 1e-6);
+            assert!(fold(x).abs() <= 1.0);
+        }
+        assert_eq!(fold(0.5), 0.5);
+        assert_eq!(fold(1.5), 0.5);
+        assert_eq!(fold(-2.5), 0.5);
+    }
+
+    #[test]
+    fn folding_aliases_less()
+    {
+        const LEN: usize = 4096;
+        const BIN: usize = 131;
+
+        // Energy outside the harmonics, which can only come from aliasing.
+        let aliasing = |mut next: Box<dyn FnMut() -> f64>| {
+            let mut x: [Complex<f64>; LEN] = core::array::from_fn(|_| Complex::new(next(), 0.0));
+            fft(&mut x);
+            x[1..LEN/2].iter()
+                .enumerate()
+                .filter(|&(k, _)| (k + 1) % BIN != 0)
+                .map(|(_, x)| x.norm_sqr())
+                .sum::<f64>()
+        };
+
+        let omega = TAU*RATE*BIN as f64/LEN as f64;
+        let mut naive = Oscillator::new(omega, 0.0, Direct::from(Sine));
+        let naive = aliasing(Box::new(move || fold(4.0*naive.next(RATE))));
+        for order in [AdaaOrder::First, AdaaOrder::Second]
+        {
+            let mut shaper = Waveshaper::new(Oscillator::new(omega, 0.0, Direct::from(Sine)), Shaper::<f64, 8>::Fold(order));
+            shaper.drive = 4.0;
+            shaper.next(RATE);
+            shaper.next(RATE);
+            let adaa = aliasing(Box::new(move || shaper.next(RATE)));
+            assert!(adaa < naive/4.0);
+        }
+
+        let mut tanh = Waveshaper::new(Oscillator::new(omega, 0.0, Direct::from(Sine)), Shaper::<f64, 8>::Tanh);
+        tanh.drive = 4.0;
+        let mut block = [0.0; 64];
+        tanh.process(RATE, &mut block);
+        assert!(block[1..].iter().all(|y| y.abs() < 1.0));
+    }
+
+    #[test]
+    fn single_precision()
+    {
+        // The inputs of the divided differences get close at low pitches, where single precision would blow them up.
+        fn shaper<F>(fold: Option<AdaaOrder>) -> Shaper<F, 8>
+        {
+            fold.map_or(Shaper::Tanh, Shaper::Fold)
+        }
+
+        for fold in [None, Some(AdaaOrder::First), Some(AdaaOrder::Second)]
+        {
+            for frequency in [20.0, 440.0, 5000.0]
+            {
+                let mut single = Waveshaper::new(Oscillator::new(core::f32::consts::TAU*frequency, 0.0, Direct::from(Sine)), shaper(fold));
+                let mut double = Waveshaper::new(Oscillator::new(TAU*frequency as f64, 0.0, Direct::from(Sine)), shaper(fold));
+                single.drive = 4.0;
+                double.drive = 4.0;
+                let mut error = 0.0f64;
+                for _ in 0..4096
+                {
+                    error = error.max((single.next(RATE as f32) as f64 - double.next(RATE)).abs());
+                }
+                assert!(error < 5e-3);
+            }
+        }
+    }
+}
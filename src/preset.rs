@@ -0,0 +1,173 @@
+use num_traits::{Float, FloatConst};
+
+use crate::{
+    oscillator::{Oscillator, OscillatorState, Switch, Wave},
+    waveform::{Waveform, WaveformExt},
+    Wavetable
+};
+
+/// The waveform of a preset, either one of the waveforms `W` or a custom wavetable.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum PresetWaveform<F, W, const N: usize>
+where
+    F: Float
+{
+    Waveform(W),
+    Wavetable(Wavetable<F, N>)
+}
+
+impl<F, W, const N: usize> Waveform<F> for PresetWaveform<F, W, N>
+where
+    F: Float,
+    W: Waveform<F>
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        match self
+        {
+            Self::Waveform(w) => w.waveform(theta),
+            Self::Wavetable(w) => Waveform::waveform(w, theta)
+        }
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        match self
+        {
+            Self::Waveform(w) => w.waveform_with_dtc(theta, duty_cycle),
+            Self::Wavetable(w) => w.waveform_with_dtc(theta, duty_cycle)
+        }
+    }
+
+    fn wavetable<const M: usize>(&self) -> Option<Wavetable<F, M>>
+    {
+        match self
+        {
+            Self::Waveform(w) => w.wavetable(),
+            Self::Wavetable(w) => Waveform::wavetable(w)
+        }
+    }
+    fn wavetable_with_dtc<const M: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, M>>
+    {
+        match self
+        {
+            Self::Waveform(w) => w.wavetable_with_dtc(duty_cycle),
+            Self::Wavetable(w) => w.wavetable_with_dtc(duty_cycle)
+        }
+    }
+}
+impl<F, W, const N: usize> WaveformExt for PresetWaveform<F, W, N>
+where
+    F: Float
+{
+}
+
+/// The first version of the oscillator preset schema.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PresetV1<F, W, const N: usize>
+where
+    F: Float
+{
+    pub waveform: PresetWaveform<F, W, N>,
+    pub duty_cycle: Option<F>,
+    /// Frequency, in Hz.
+    pub frequency: F,
+    /// Phase offset, in radians.
+    pub phase: F
+}
+
+/// The latest version of the oscillator preset schema.
+pub type OscillatorPreset<F, W, const N: usize> = PresetV1<F, W, N>;
+
+/// An oscillator preset of any version, tagged with its version when serialized.
+///
+/// When the schema changes, a new version is added as a variant, and [`Preset::migrate`] learns to upgrade the older ones, so
+/// presets saved with older versions of the crate keep loading.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "version")]
+pub enum Preset<F, W, const N: usize>
+where
+    F: Float
+{
+    V1(PresetV1<F, W, N>)
+}
+
+impl<F, W, const N: usize> Preset<F, W, N>
+where
+    F: Float
+{
+    /// Upgrades the preset to the latest version of the schema.
+    pub fn migrate(self) -> OscillatorPreset<F, W, N>
+    {
+        match self
+        {
+            Self::V1(preset) => preset
+        }
+    }
+}
+
+impl<F, W, const N: usize> From<OscillatorPreset<F, W, N>> for Preset<F, W, N>
+where
+    F: Float
+{
+    fn from(preset: OscillatorPreset<F, W, N>) -> Self
+    {
+        Self::V1(preset)
+    }
+}
+
+impl<F, W, const N: usize> PresetV1<F, W, N>
+where
+    F: Float + FloatConst,
+    W: Waveform<F>
+{
+    /// Creates a band-limited oscillator playing the preset.
+    pub fn oscillator<const M: usize>(self) -> Oscillator<F, Switch<F, PresetWaveform<F, W, N>, M>>
+    {
+        let Self { waveform, duty_cycle, frequency, phase } = self;
+        let mut state = Switch::from(Wave::from(waveform));
+        if let Some(duty_cycle) = duty_cycle
+        {
+            state = state.with_dtc(duty_cycle)
+        }
+        Oscillator::new(F::TAU()*frequency, phase, state)
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use crate::{waveform::MekkaWaveform, Wavetable};
+
+    use super::{Preset, PresetV1, PresetWaveform};
+
+    #[test]
+    fn it_works()
+    {
+        let presets = [
+            PresetV1 {
+                waveform: PresetWaveform::Waveform(MekkaWaveform::Square),
+                duty_cycle: Some(0.25),
+                frequency: 440.0,
+                phase: 0.0
+            },
+            PresetV1 {
+                waveform: PresetWaveform::Wavetable(Wavetable::from_fn(0.0, |n| (0.0, 1.0/(n + 1) as f32))),
+                duty_cycle: None,
+                frequency: 110.0,
+                phase: 1.0
+            }
+        ];
+
+        for preset in presets
+        {
+            let json = serde_json::to_string(&Preset::from(preset)).unwrap();
+            assert!(json.starts_with(r#"{"version":"V1""#));
+
+            let loaded: Preset<f32, MekkaWaveform, 16> = serde_json::from_str(&json).unwrap();
+            assert_eq!(loaded.migrate(), preset);
+
+            let mut osc = loaded.migrate().oscillator::<64>();
+            assert!(osc.next(44100.0).is_finite());
+        }
+    }
+}
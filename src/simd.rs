@@ -0,0 +1,47 @@
+use core::{
+    ops::{Add, AddAssign, Div, Mul, Neg, Sub},
+    simd::{Select, Simd, SimdElement, cmp::SimdPartialOrd, num::{SimdFloat, SimdInt}}
+};
+
+/// Lane-wise float operations needed to evaluate waveforms on [`Simd`] vectors.
+///
+/// Implemented for `Simd<f32, L>` and `Simd<f64, L>`.
+pub trait FloatLanes<F, const L: usize>:
+    Copy + Add<Output = Self> + AddAssign + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self> + Neg<Output = Self>
+where
+    F: SimdElement
+{
+    fn abs(self) -> Self;
+    fn floor(self) -> Self;
+    fn rem_euclid(self, rhs: Self) -> Self
+    {
+        self - (self/rhs).floor()*rhs
+    }
+    /// Picks `if_true` in lanes where `self < rhs`, and `if_false` elsewhere.
+    fn select_lt(self, rhs: Self, if_true: Self, if_false: Self) -> Self;
+}
+
+macro_rules! impl_float_lanes {
+    ($($f:ty: $i:ty),*) => {
+        $(
+            impl<const L: usize> FloatLanes<$f, L> for Simd<$f, L>
+            {
+                fn abs(self) -> Self
+                {
+                    SimdFloat::abs(self)
+                }
+                fn floor(self) -> Self
+                {
+                    let t = self.cast::<$i>().cast::<$f>();
+                    self.simd_lt(t).select(t - Simd::splat(1.0), t)
+                }
+                fn select_lt(self, rhs: Self, if_true: Self, if_false: Self) -> Self
+                {
+                    self.simd_lt(rhs).select(if_true, if_false)
+                }
+            }
+        )*
+    };
+}
+
+impl_float_lanes!(f32: i32, f64: i64);
@@ -64,4 +64,63 @@ where
     Lhs: Mul<Rhs, Output = Lhs>
 {
     MulAssignSpec::mul_assign(lhs, rhs);
-}
\ No newline at end of file
+}
+
+/// Serde for arrays of any length, for `#[serde(with = "crate::util::serde_array")]`. Serde itself only covers up to 32 elements.
+pub mod serde_array
+{
+    use core::{fmt, marker::PhantomData};
+
+    use serde::{
+        de::{self, SeqAccess, Visitor},
+        ser::SerializeTuple,
+        Deserialize, Deserializer, Serialize, Serializer
+    };
+
+    pub fn serialize<S, T, const K: usize>(array: &[T; K], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize
+    {
+        let mut tuple = serializer.serialize_tuple(K)?;
+        for x in array
+        {
+            tuple.serialize_element(x)?;
+        }
+        tuple.end()
+    }
+
+    pub fn deserialize<'de, D, T, const K: usize>(deserializer: D) -> Result<[T; K], D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>
+    {
+        struct ArrayVisitor<T, const K: usize>(PhantomData<T>);
+
+        impl<'de, T, const K: usize> Visitor<'de> for ArrayVisitor<T, K>
+        where
+            T: Deserialize<'de>
+        {
+            type Value = [T; K];
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result
+            {
+                write!(f, "an array of length {}", K)
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>
+            {
+                let mut array: [Option<T>; K] = core::array::from_fn(|_| None);
+                for (i, x) in array.iter_mut().enumerate()
+                {
+                    *x = Some(seq.next_element()?.ok_or_else(|| de::Error::invalid_length(i, &self))?);
+                }
+                Ok(array.map(Option::unwrap))
+            }
+        }
+
+        deserializer.deserialize_tuple(K, ArrayVisitor(PhantomData))
+    }
+}
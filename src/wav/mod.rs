@@ -0,0 +1,97 @@
+use core::fmt;
+
+moddef::moddef!(
+    flat(pub) mod {
+        reader,
+        writer
+    }
+);
+
+const RIFF: [u8; 4] = *b"RIFF";
+const WAVE: [u8; 4] = *b"WAVE";
+const FMT: [u8; 4] = *b"fmt ";
+const DATA: [u8; 4] = *b"data";
+const CLM: [u8; 4] = *b"clm ";
+const FACT: [u8; 4] = *b"fact";
+
+const WAVE_FORMAT_PCM: u16 = 0x0001;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Frame length of multi-frame wavetable files without a `clm ` chunk.
+pub const FRAME_LEN_DEFAULT: usize = 2048;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+pub enum SampleFormat
+{
+    U8,
+    I16,
+    I24,
+    I32,
+    F32,
+    F64
+}
+
+impl SampleFormat
+{
+    pub const fn bytes(self) -> usize
+    {
+        match self
+        {
+            Self::U8 => 1,
+            Self::I16 => 2,
+            Self::I24 => 3,
+            Self::I32 | Self::F32 => 4,
+            Self::F64 => 8
+        }
+    }
+
+    const fn format_tag(self) -> u16
+    {
+        match self
+        {
+            Self::U8 | Self::I16 | Self::I24 | Self::I32 => WAVE_FORMAT_PCM,
+            Self::F32 | Self::F64 => WAVE_FORMAT_IEEE_FLOAT
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WavError
+{
+    NotRiff,
+    NotWave,
+    Truncated,
+    MissingFormat,
+    MissingData,
+    UnsupportedFormat(u16),
+    UnsupportedBitDepth(u16),
+    NoChannels,
+    Empty,
+    BufferFull
+}
+
+impl fmt::Display for WavError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self
+        {
+            Self::NotRiff => write!(f, "Not a RIFF file."),
+            Self::NotWave => write!(f, "Not a WAVE file."),
+            Self::Truncated => write!(f, "File is truncated."),
+            Self::MissingFormat => write!(f, "Missing 'fmt ' chunk."),
+            Self::MissingData => write!(f, "Missing 'data' chunk."),
+            Self::UnsupportedFormat(tag) => write!(f, "Unsupported format tag {:#06x}.", tag),
+            Self::UnsupportedBitDepth(bits) => write!(f, "Unsupported bit depth {}.", bits),
+            Self::NoChannels => write!(f, "File has no channels."),
+            Self::Empty => write!(f, "File has no samples."),
+            Self::BufferFull => write!(f, "Buffer is full.")
+        }
+    }
+}
+
+impl core::error::Error for WavError
+{
+
+}
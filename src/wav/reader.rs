@@ -0,0 +1,312 @@
+use num_traits::{Float, FloatConst};
+
+use crate::Wavetable;
+
+use super::{SampleFormat, WavError, CLM, DATA, FMT, FRAME_LEN_DEFAULT, RIFF, WAVE, WAVE_FORMAT_EXTENSIBLE, WAVE_FORMAT_IEEE_FLOAT, WAVE_FORMAT_PCM};
+
+/// Reads single-cycle waveforms and multi-frame wavetables from the bytes of a RIFF/WAVE file.
+///
+/// Only the first channel is used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WavReader<'a>
+{
+    pub format: SampleFormat,
+    pub channels: u16,
+    pub sample_rate: u32,
+    clm: Option<usize>,
+    data: &'a [u8]
+}
+
+impl<'a> WavReader<'a>
+{
+    pub fn new(bytes: &'a [u8]) -> Result<Self, WavError>
+    {
+        if bytes.get(0..4) != Some(&RIFF)
+        {
+            return Err(WavError::NotRiff)
+        }
+        if bytes.get(8..12) != Some(&WAVE)
+        {
+            return Err(WavError::NotWave)
+        }
+
+        let mut fmt = None;
+        let mut data = None;
+        let mut clm = None;
+
+        let mut chunks = &bytes[12..];
+        while chunks.len() >= 8
+        {
+            let id = &chunks[0..4];
+            let size = u32_le(&chunks[4..8]) as usize;
+            let body = &chunks[8..];
+            let body = &body[..size.min(body.len())];
+
+            match id.try_into()
+            {
+                Ok(FMT) => fmt = Some(Self::parse_fmt(body)?),
+                Ok(DATA) => data = Some(body),
+                Ok(CLM) => clm = parse_clm(body),
+                _ => ()
+            }
+
+            // The size comes from the file, so it could overflow on 32-bit targets.
+            let next = size.checked_add(8 + size % 2).ok_or(WavError::Truncated)?;
+            chunks = chunks.get(next..).unwrap_or(&[]);
+        }
+
+        let (format, channels, sample_rate) = fmt.ok_or(WavError::MissingFormat)?;
+        let data = data.ok_or(WavError::MissingData)?;
+        if channels == 0
+        {
+            return Err(WavError::NoChannels)
+        }
+
+        let reader = Self {
+            format,
+            channels,
+            sample_rate,
+            clm,
+            data
+        };
+        if reader.is_empty()
+        {
+            return Err(WavError::Empty)
+        }
+        Ok(reader)
+    }
+
+    fn parse_fmt(body: &[u8]) -> Result<(SampleFormat, u16, u32), WavError>
+    {
+        if body.len() < 16
+        {
+            return Err(WavError::Truncated)
+        }
+        let mut tag = u16_le(&body[0..2]);
+        let channels = u16_le(&body[2..4]);
+        let sample_rate = u32_le(&body[4..8]);
+        let bits = u16_le(&body[14..16]);
+
+        if tag == WAVE_FORMAT_EXTENSIBLE
+        {
+            tag = body.get(24..26)
+                .map(u16_le)
+                .ok_or(WavError::Truncated)?;
+        }
+
+        let format = match (tag, bits)
+        {
+            (WAVE_FORMAT_PCM, 8) => SampleFormat::U8,
+            (WAVE_FORMAT_PCM, 16) => SampleFormat::I16,
+            (WAVE_FORMAT_PCM, 24) => SampleFormat::I24,
+            (WAVE_FORMAT_PCM, 32) => SampleFormat::I32,
+            (WAVE_FORMAT_IEEE_FLOAT, 32) => SampleFormat::F32,
+            (WAVE_FORMAT_IEEE_FLOAT, 64) => SampleFormat::F64,
+            (WAVE_FORMAT_PCM | WAVE_FORMAT_IEEE_FLOAT, bits) => return Err(WavError::UnsupportedBitDepth(bits)),
+            (tag, _) => return Err(WavError::UnsupportedFormat(tag))
+        };
+
+        Ok((format, channels, sample_rate))
+    }
+
+    /// Number of samples per channel.
+    pub fn len(&self) -> usize
+    {
+        self.data.len()/self.stride()
+    }
+    pub fn is_empty(&self) -> bool
+    {
+        self.len() == 0
+    }
+
+    /// Number of samples in each frame of the wavetable.
+    ///
+    /// This is given by the `clm ` chunk if there is one. Otherwise, files that are a whole number of [`FRAME_LEN_DEFAULT`]-sample frames
+    /// are read as multi-frame wavetables, and anything else as a single-cycle waveform.
+    pub fn frame_len(&self) -> usize
+    {
+        let len = self.len();
+        match self.clm
+        {
+            Some(frame_len) if frame_len != 0 => frame_len.min(len),
+            _ if len.is_multiple_of(FRAME_LEN_DEFAULT) => FRAME_LEN_DEFAULT,
+            _ => len
+        }
+    }
+    pub fn frame_count(&self) -> usize
+    {
+        self.len()/self.frame_len()
+    }
+
+    /// Samples of the first channel.
+    pub fn samples<F>(&self) -> impl ExactSizeIterator<Item = F> + Clone + 'a
+    where
+        F: Float
+    {
+        let format = self.format;
+        self.data.chunks_exact(self.stride())
+            .map(move |sample| decode(format, sample))
+    }
+
+    pub fn frame<F>(&self, frame: usize) -> Option<impl ExactSizeIterator<Item = F> + Clone + 'a>
+    where
+        F: Float
+    {
+        if frame >= self.frame_count()
+        {
+            return None
+        }
+        let frame_len = self.frame_len();
+        Some(self.samples().skip(frame*frame_len).take(frame_len))
+    }
+
+    /// The band-limited wavetable of one frame.
+    pub fn wavetable<F, const N: usize>(&self, frame: usize) -> Option<Wavetable<F, N>>
+    where
+        F: Float + FloatConst
+    {
+        self.frame(frame).map(Wavetable::from_samples)
+    }
+
+    /// The band-limited wavetables of all frames.
+    pub fn wavetables<F, const N: usize>(&self) -> impl ExactSizeIterator<Item = Wavetable<F, N>> + '_
+    where
+        F: Float + FloatConst
+    {
+        let frame_len = self.frame_len();
+        (0..self.frame_count()).map(move |frame| Wavetable::from_samples(self.samples().skip(frame*frame_len).take(frame_len)))
+    }
+
+    fn stride(&self) -> usize
+    {
+        self.format.bytes()*self.channels as usize
+    }
+}
+
+fn decode<F>(format: SampleFormat, sample: &[u8]) -> F
+where
+    F: Float
+{
+    let x = match format
+    {
+        SampleFormat::U8 => (sample[0] as f64 - 128.0)/128.0,
+        SampleFormat::I16 => i16::from_le_bytes([sample[0], sample[1]]) as f64/32768.0,
+        SampleFormat::I24 => (i32::from_le_bytes([0, sample[0], sample[1], sample[2]]) >> 8) as f64/8388608.0,
+        SampleFormat::I32 => i32::from_le_bytes([sample[0], sample[1], sample[2], sample[3]]) as f64/2147483648.0,
+        SampleFormat::F32 => f32::from_le_bytes([sample[0], sample[1], sample[2], sample[3]]) as f64,
+        SampleFormat::F64 => f64::from_le_bytes(core::array::from_fn(|i| sample[i]))
+    };
+    F::from(x).unwrap()
+}
+
+/// Reads the frame length from a `clm ` chunk, which starts like `<!>2048`.
+fn parse_clm(body: &[u8]) -> Option<usize>
+{
+    let digits = body.strip_prefix(b"<!>")?;
+    let digits = &digits[..digits.iter().position(|c| !c.is_ascii_digit()).unwrap_or(digits.len())];
+    core::str::from_utf8(digits).ok()?.parse().ok()
+}
+
+fn u16_le(bytes: &[u8]) -> u16
+{
+    u16::from_le_bytes([bytes[0], bytes[1]])
+}
+fn u32_le(bytes: &[u8]) -> u32
+{
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f64::consts::TAU;
+
+    use crate::wav::SampleFormat;
+
+    use super::WavReader;
+
+    fn wav(tag: u16, bits: u16, data: &[u8], clm: Option<&str>) -> Vec<u8>
+    {
+        let mut chunks = vec![];
+        chunks.extend(b"fmt ");
+        chunks.extend(16u32.to_le_bytes());
+        chunks.extend(tag.to_le_bytes());
+        chunks.extend(1u16.to_le_bytes());
+        chunks.extend(44100u32.to_le_bytes());
+        chunks.extend((44100*bits as u32/8).to_le_bytes());
+        chunks.extend((bits/8).to_le_bytes());
+        chunks.extend(bits.to_le_bytes());
+        if let Some(clm) = clm
+        {
+            chunks.extend(b"clm ");
+            chunks.extend((clm.len() as u32).to_le_bytes());
+            chunks.extend(clm.as_bytes());
+            if clm.len() % 2 == 1
+            {
+                chunks.push(0);
+            }
+        }
+        chunks.extend(b"data");
+        chunks.extend((data.len() as u32).to_le_bytes());
+        chunks.extend(data);
+
+        let mut bytes = vec![];
+        bytes.extend(b"RIFF");
+        bytes.extend((4 + chunks.len() as u32).to_le_bytes());
+        bytes.extend(b"WAVE");
+        bytes.extend(chunks);
+        bytes
+    }
+
+    #[test]
+    fn single_cycle()
+    {
+        const LEN: usize = 600;
+
+        let data: Vec<u8> = (0..LEN).map(|k| {
+                let theta = TAU*k as f64/LEN as f64;
+                ((0.5*theta.cos() + 0.25*(3.0*theta).sin())*32767.0) as i16
+            })
+            .flat_map(i16::to_le_bytes)
+            .collect();
+        let bytes = wav(1, 16, &data, None);
+        let wav = WavReader::new(&bytes).unwrap();
+        assert_eq!(wav.format, SampleFormat::I16);
+        assert_eq!(wav.frame_count(), 1);
+
+        let wavetable = wav.wavetable::<f64, 8>(0).unwrap();
+        let view = wavetable.view();
+        assert!(view.a0.abs() < 1e-3);
+        for (n, &(a, b)) in view.ab.iter().enumerate()
+        {
+            let (a_n, b_n) = match n + 1
+            {
+                1 => (0.5, 0.0),
+                3 => (0.0, 0.25),
+                _ => (0.0, 0.0)
+            };
+            assert!((a - a_n).abs() < 1e-3 && (b - b_n).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn multi_frame()
+    {
+        const FRAME_LEN: usize = 256;
+        const FRAMES: usize = 4;
+
+        let data: Vec<u8> = (0..FRAMES*FRAME_LEN).map(|k| (k/FRAME_LEN) as f32*(TAU*k as f64/FRAME_LEN as f64).cos() as f32)
+            .flat_map(f32::to_le_bytes)
+            .collect();
+        let bytes = wav(3, 32, &data, Some("<!>256 10000000 wavetable"));
+        let wav = WavReader::new(&bytes).unwrap();
+        assert_eq!(wav.format, SampleFormat::F32);
+        assert_eq!(wav.frame_len(), FRAME_LEN);
+        assert_eq!(wav.frame_count(), FRAMES);
+
+        for (frame, wavetable) in wav.wavetables::<f32, 4>().enumerate()
+        {
+            assert!((wavetable.view().ab[0].0 - frame as f32).abs() < 1e-3);
+        }
+    }
+}
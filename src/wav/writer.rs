@@ -0,0 +1,206 @@
+use num_traits::{Euclid, Float, FloatConst};
+
+use crate::{
+    oscillator::{Oscillator, OscillatorState},
+    waveform::Waveform
+};
+
+use super::{SampleFormat, WavError, DATA, FACT, FMT, RIFF, WAVE};
+
+/// Somewhere to write the bytes of a WAVE file, like [`std::io::Write`].
+pub trait WavSink
+{
+    type Error;
+
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// Writes into the start of the slice, and advances past what has been written.
+impl WavSink for &mut [u8]
+{
+    type Error = WavError;
+
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), Self::Error>
+    {
+        if bytes.len() > self.len()
+        {
+            return Err(WavError::BufferFull)
+        }
+        let (head, tail) = core::mem::take(self).split_at_mut(bytes.len());
+        head.copy_from_slice(bytes);
+        *self = tail;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+pub struct WavSpec
+{
+    pub format: SampleFormat,
+    pub sample_rate: u32,
+    pub channels: u16
+}
+
+impl WavSpec
+{
+    /// Size of the whole file, in bytes, for `samples` interleaved samples.
+    pub const fn file_len(&self, samples: usize) -> usize
+    {
+        let data = samples*self.format.bytes();
+        12 + self.fmt_len() + 8 + self.fact_len() + 8 + data + data % 2
+    }
+
+    const fn is_float(&self) -> bool
+    {
+        matches!(self.format, SampleFormat::F32 | SampleFormat::F64)
+    }
+    const fn fmt_len(&self) -> usize
+    {
+        if self.is_float() {18} else {16}
+    }
+    const fn fact_len(&self) -> usize
+    {
+        if self.is_float() {4 + 8} else {0}
+    }
+}
+
+/// Writes a WAVE file of interleaved `samples`, returning how many bytes were written.
+///
+/// Integer formats clip samples outside `[-1, 1]`.
+pub fn write_wav<F, S>(sink: &mut S, spec: WavSpec, samples: impl ExactSizeIterator<Item = F>) -> Result<usize, S::Error>
+where
+    F: Float,
+    S: WavSink
+{
+    let len = samples.len();
+    let file_len = spec.file_len(len);
+    let data_len = len*spec.format.bytes();
+    let block_align = spec.channels as usize*spec.format.bytes();
+    let bits = 8*spec.format.bytes() as u16;
+
+    sink.write_all(&RIFF)?;
+    sink.write_all(&(file_len as u32 - 8).to_le_bytes())?;
+    sink.write_all(&WAVE)?;
+
+    sink.write_all(&FMT)?;
+    sink.write_all(&(spec.fmt_len() as u32).to_le_bytes())?;
+    sink.write_all(&spec.format.format_tag().to_le_bytes())?;
+    sink.write_all(&spec.channels.to_le_bytes())?;
+    sink.write_all(&spec.sample_rate.to_le_bytes())?;
+    sink.write_all(&(spec.sample_rate*block_align as u32).to_le_bytes())?;
+    sink.write_all(&(block_align as u16).to_le_bytes())?;
+    sink.write_all(&bits.to_le_bytes())?;
+    if spec.is_float()
+    {
+        sink.write_all(&0u16.to_le_bytes())?;
+
+        sink.write_all(&FACT)?;
+        sink.write_all(&4u32.to_le_bytes())?;
+        sink.write_all(&((len/spec.channels.max(1) as usize) as u32).to_le_bytes())?;
+    }
+
+    sink.write_all(&DATA)?;
+    sink.write_all(&(data_len as u32).to_le_bytes())?;
+    for x in samples
+    {
+        encode(spec.format, x, |bytes| sink.write_all(bytes))?;
+    }
+    if data_len % 2 == 1
+    {
+        sink.write_all(&[0])?;
+    }
+
+    Ok(file_len)
+}
+
+/// The next `len` samples of an oscillator.
+pub fn render<'a, F, S>(oscillator: &'a mut Oscillator<F, S>, rate: F, len: usize) -> impl ExactSizeIterator<Item = F> + 'a
+where
+    F: Float + FloatConst + Euclid + 'a,
+    S: OscillatorState<F>
+{
+    (0..len).map(move |_| oscillator.next(rate))
+}
+
+/// One cycle of a waveform, sampled `len` times over `[0, 2π)`.
+pub fn render_cycle<'a, F, W>(waveform: &'a W, len: usize) -> impl ExactSizeIterator<Item = F> + 'a
+where
+    F: Float + FloatConst + 'a,
+    W: Waveform<F>
+{
+    let dtheta = F::TAU()/F::from(len).unwrap();
+    (0..len).map(move |k| waveform.waveform(dtheta*F::from(k).unwrap()))
+}
+
+fn encode<F, E>(format: SampleFormat, x: F, mut write: impl FnMut(&[u8]) -> Result<(), E>) -> Result<(), E>
+where
+    F: Float
+{
+    let x = x.to_f64().unwrap_or(0.0);
+    let int = |max: f64| (x.clamp(-1.0, 1.0)*max).round() as i64;
+    match format
+    {
+        SampleFormat::U8 => write(&[(int(127.0) + 128) as u8]),
+        SampleFormat::I16 => write(&(int(i16::MAX as f64) as i16).to_le_bytes()),
+        SampleFormat::I24 => write(&(int(8388607.0) as i32).to_le_bytes()[..3]),
+        SampleFormat::I32 => write(&(int(i32::MAX as f64) as i32).to_le_bytes()),
+        SampleFormat::F32 => write(&(x as f32).to_le_bytes()),
+        SampleFormat::F64 => write(&x.to_le_bytes())
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f32::consts::TAU;
+
+    use crate::{
+        oscillator::{Direct, Oscillator},
+        wav::{SampleFormat, WavReader},
+        waveform::{Sawtooth, Sine}
+    };
+
+    use super::{render, render_cycle, write_wav, WavSpec};
+
+    #[test]
+    fn round_trip()
+    {
+        const LEN: usize = 101;
+        const RATE: f32 = 44100.0;
+
+        for (format, tolerance) in [(SampleFormat::I16, 1e-4), (SampleFormat::I24, 1e-6), (SampleFormat::F32, 0.0)]
+        {
+            let spec = WavSpec {
+                format,
+                sample_rate: RATE as u32,
+                channels: 1
+            };
+
+            let mut osc = Oscillator::new(TAU*441.0, 0.0, Direct::from(Sine));
+            let mut bytes = vec![0; spec.file_len(LEN)];
+            let len = write_wav(&mut bytes.as_mut_slice(), spec, render(&mut osc, RATE, LEN)).unwrap();
+            assert_eq!(len, bytes.len());
+
+            let wav = WavReader::new(&bytes).unwrap();
+            assert_eq!(wav.format, format);
+            assert_eq!(wav.sample_rate, spec.sample_rate);
+
+            let mut osc = Oscillator::new(TAU*441.0, 0.0, Direct::from(Sine));
+            for (x, y) in wav.samples::<f32>().zip(render(&mut osc, RATE, LEN))
+            {
+                assert!((x - y).abs() <= tolerance);
+            }
+        }
+
+        let spec = WavSpec {
+            format: SampleFormat::F32,
+            sample_rate: RATE as u32,
+            channels: 1
+        };
+        let mut bytes = [0; 1024];
+        write_wav(&mut bytes.as_mut_slice(), spec, render_cycle::<f32, _>(&Sawtooth, 64)).unwrap();
+        let wav = WavReader::new(&bytes).unwrap();
+        assert_eq!(wav.len(), 64);
+        assert!(wav.samples::<f32>().eq(render_cycle::<f32, _>(&Sawtooth, 64)));
+    }
+}
@@ -0,0 +1,455 @@
+use num_complex::Complex;
+use num_traits::{Euclid, Float, FloatConst};
+
+use crate::Wavetable;
+
+use super::{breakpoints, Waveform, WaveformExt};
+
+/// How smoothly the segments of a [`Bezier`] waveform are joined.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+pub enum Continuity
+{
+    /// Segments may jump.
+    #[default]
+    None,
+    /// Segments meet.
+    C0,
+    /// Segments meet with the same slope.
+    C1
+}
+
+/// One segment of a [`Bezier`] waveform, starting at phase `theta` and ending where the next one starts.
+///
+/// The value is a cubic Bézier curve over the segment, with the control values `y`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+pub struct BezierSegment<F>
+{
+    /// Phase, in radians.
+    pub theta: F,
+    pub y: [F; 4]
+}
+
+impl<F> BezierSegment<F>
+where
+    F: Float
+{
+    pub fn linear(theta: F, y0: F, y1: F) -> Self
+    {
+        let three = F::from(3.0).unwrap();
+        Self::cubic(theta, [y0, (y0 + y0 + y1)/three, (y0 + y1 + y1)/three, y1])
+    }
+
+    pub fn quadratic(theta: F, [y0, y1, y2]: [F; 3]) -> Self
+    {
+        let three = F::from(3.0).unwrap();
+        Self::cubic(theta, [y0, (y0 + y1 + y1)/three, (y1 + y1 + y2)/three, y2])
+    }
+
+    pub fn cubic(theta: F, y: [F; 4]) -> Self
+    {
+        Self {
+            theta,
+            y
+        }
+    }
+
+    /// A polynomial `c[0] + c[1]·t + c[2]·t² + c[3]·t³`, where `t` goes from zero to one over the segment.
+    pub fn polynomial(theta: F, [c0, c1, c2, c3]: [F; 4]) -> Self
+    {
+        let three = F::from(3.0).unwrap();
+        Self::cubic(theta, [
+            c0,
+            c0 + c1/three,
+            c0 + (c1 + c1 + c2)/three,
+            c0 + c1 + c2 + c3
+        ])
+    }
+
+    fn value(&self, t: F) -> F
+    {
+        let [y0, y1, y2, y3] = self.y;
+        let s = F::one() - t;
+        let three = F::from(3.0).unwrap();
+        s*s*s*y0 + three*s*t*(s*y1 + t*y2) + t*t*t*y3
+    }
+
+    /// Splits the curve at `t`, with de Casteljau's algorithm.
+    fn split(y: [F; 4], t: F) -> ([F; 4], [F; 4])
+    {
+        let lerp = |a: F, b: F| a + (b - a)*t;
+        let [y0, y1, y2, y3] = y;
+        let (y01, y12, y23) = (lerp(y0, y1), lerp(y1, y2), lerp(y2, y3));
+        let (y012, y123) = (lerp(y01, y12), lerp(y12, y23));
+        let y0123 = lerp(y012, y123);
+        ([y0, y01, y012, y0123], [y0123, y123, y23, y3])
+    }
+}
+
+/// A waveform drawn with `K` cubic Bézier segments, like an editable [`RoundedTriangle`](super::RoundedTriangle).
+///
+/// The wavetable is exact, since polynomials integrate against the harmonics in closed form, by integrating by parts until the
+/// derivative vanishes. With a duty cycle, the phases are warped like for [`Breakpoints`](super::Breakpoints).
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+#[serde(
+    bound(serialize = "F: serde::Serialize", deserialize = "F: Float + FloatConst + Euclid + serde::Deserialize<'de>"),
+    from = "BezierSerde<F, K>"
+)]
+pub struct Bezier<F, const K: usize>
+{
+    #[serde(with = "crate::util::serde_array")]
+    segments: [BezierSegment<F>; K],
+    continuity: Continuity
+}
+
+/// The segments as they're stored, before [`Bezier::new`] sorts and joins them.
+#[derive(serde::Deserialize)]
+#[serde(bound(deserialize = "F: serde::Deserialize<'de>"))]
+struct BezierSerde<F, const K: usize>
+{
+    #[serde(with = "crate::util::serde_array")]
+    segments: [BezierSegment<F>; K],
+    continuity: Continuity
+}
+
+impl<F, const K: usize> From<BezierSerde<F, K>> for Bezier<F, K>
+where
+    F: Float + FloatConst + Euclid
+{
+    fn from(value: BezierSerde<F, K>) -> Self
+    {
+        Self::new(value.segments, value.continuity)
+    }
+}
+
+impl<F, const K: usize> Bezier<F, K>
+where
+    F: Float + FloatConst + Euclid
+{
+    /// Wraps the phases of the segments into `[0, 2π)`, sorts them by phase, and joins them as smoothly as `continuity` asks, by
+    /// meeting them halfway.
+    pub fn new(mut segments: [BezierSegment<F>; K], continuity: Continuity) -> Self
+    {
+        let tau = F::TAU();
+        for segment in segments.iter_mut()
+        {
+            segment.theta = segment.theta.rem_euclid(&tau);
+        }
+        for i in 1..K
+        {
+            let mut j = i;
+            while j > 0 && segments[j - 1].theta > segments[j].theta
+            {
+                segments.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+
+        let mut bezier = Self {
+            segments,
+            continuity
+        };
+        for i in 0..K
+        {
+            bezier.join(i, None);
+        }
+        bezier
+    }
+
+    pub fn segments(&self) -> &[BezierSegment<F>; K]
+    {
+        &self.segments
+    }
+    pub fn continuity(&self) -> Continuity
+    {
+        self.continuity
+    }
+
+    /// Changes the values of a segment, keeping its phase. Its neighbours are adjusted to keep the continuity.
+    pub fn set_segment(&mut self, i: usize, y: [F; 4])
+    {
+        self.segments[i].y = y;
+        self.join(i, Some(i));
+        self.join((i + 1) % K, Some(i));
+    }
+
+    /// Span of the `i`-th segment, from its phase to the next one's. The last one wraps around.
+    fn span(&self, i: usize) -> (F, F)
+    {
+        let t0 = self.segments[i].theta;
+        let t1 = match self.segments.get(i + 1)
+        {
+            Some(next) => next.theta,
+            None => self.segments[0].theta + F::TAU()
+        };
+        (t0, t1)
+    }
+
+    /// Joins the end of the segment before `i` to the start of segment `i`, leaving segment `keep` as is.
+    fn join(&mut self, i: usize, keep: Option<usize>)
+    {
+        if self.continuity == Continuity::None
+        {
+            return
+        }
+        let two = F::one() + F::one();
+        let h = (i + K - 1) % K;
+        let keep = keep.filter(|_| h != i);
+
+        let (l, r) = (self.segments[h].y, self.segments[i].y);
+        let y = match keep
+        {
+            Some(k) if k == h => l[3],
+            Some(_) => r[0],
+            None => (l[3] + r[0])/two
+        };
+        self.segments[h].y[3] = y;
+        self.segments[i].y[0] = y;
+
+        if self.continuity == Continuity::C1
+        {
+            let (t0, t1) = self.span(h);
+            let dl = t1 - t0;
+            let (t0, t1) = self.span(i);
+            let dr = t1 - t0;
+            if dl.is_nan() || dr.is_nan() || dl <= F::zero() || dr <= F::zero()
+            {
+                return
+            }
+
+            let slope_l = (l[3] - l[2])/dl;
+            let slope_r = (r[1] - r[0])/dr;
+            let slope = match keep
+            {
+                Some(k) if k == h => slope_l,
+                Some(_) => slope_r,
+                None => (slope_l + slope_r)/two
+            };
+            self.segments[h].y[2] = y - slope*dl;
+            self.segments[i].y[1] = y + slope*dr;
+        }
+    }
+
+    fn harmonics<const N: usize>(&self, warp: impl Fn(F) -> F) -> Wavetable<F, N>
+    {
+        let zero = F::zero();
+        let pi = F::PI();
+        let three = F::from(3.0).unwrap();
+        let six = three + three;
+
+        let mut a0 = zero;
+        let mut ab = [(zero, zero); N];
+        let mut add = |t0: F, t1: F, [y0, y1, y2, y3]: [F; 4]| {
+            let (t0, t1) = (warp(t0), warp(t1));
+            let dt = t1 - t0;
+            if dt.is_nan() || dt <= zero
+            {
+                return
+            }
+            a0 = a0 + (y0 + y1 + y2 + y3)*dt;
+
+            // Derivatives at each end, with respect to the phase.
+            let d3 = six*(y3 - three*(y2 - y1) - y0)/(dt*dt*dt);
+            let start = [y0, three*(y1 - y0)/dt, six*(y2 - y1 - y1 + y0)/(dt*dt), d3];
+            let end = [y3, three*(y3 - y2)/dt, six*(y3 - y2 - y2 + y1)/(dt*dt), d3];
+
+            // ∫p(θ)e^(-inθ)dθ = e^(-inθ)Σ(-1)ᵏp⁽ᵏ⁾(θ)/(-in)ᵏ⁺¹
+            let antiderivative = |theta: F, d: [F; 4], n: F| {
+                let q = Complex::new(zero, n.recip());
+                let mut qk = q;
+                let mut sum = Complex::new(zero, zero);
+                for (k, d) in d.into_iter().enumerate()
+                {
+                    sum = sum + if k % 2 == 0 {qk*d} else {-qk*d};
+                    qk = qk*q;
+                }
+                Complex::cis(-n*theta)*sum
+            };
+            for (k, (a, b)) in ab.iter_mut().enumerate()
+            {
+                let n = F::from(k + 1).unwrap();
+                let c = antiderivative(t1, end, n) - antiderivative(t0, start, n);
+                *a = *a + c.re;
+                *b = *b - c.im;
+            }
+        };
+
+        for i in 0..K
+        {
+            let (t0, t1) = self.span(i);
+
+            // Split where the warp bends, so each piece stays a polynomial in the warped phase.
+            let (mut start, mut y) = (t0, self.segments[i].y);
+            for cut in [pi, pi + pi, pi + pi + pi]
+            {
+                if start < cut && cut < t1
+                {
+                    let (left, right) = BezierSegment::split(y, (cut - start)/(t1 - start));
+                    add(start, cut, left);
+                    (start, y) = (cut, right);
+                }
+            }
+            add(start, t1, y);
+        }
+
+        let four = F::from(4.0).unwrap();
+        Wavetable::from_array(a0/(four*F::TAU()), ab.map(|(a, b)| (a/pi, b/pi)))
+    }
+}
+
+impl<F, const K: usize> Waveform<F> for Bezier<F, K>
+where
+    F: Float + FloatConst + Euclid
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        let tau = F::TAU();
+
+        let theta = theta.rem_euclid(&tau);
+        let (i, theta) = match self.segments.iter().rposition(|segment| segment.theta <= theta)
+        {
+            Some(i) => (i, theta),
+            None if K == 0 => return F::zero(),
+            None => (K - 1, theta + tau)
+        };
+
+        let (t0, t1) = self.span(i);
+        let t = if t1 > t0 {(theta - t0)/(t1 - t0)} else {F::zero()};
+        self.segments[i].value(t)
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        self.waveform(breakpoints::dtc_unwarp(theta, duty_cycle))
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        Some(self.harmonics(|theta| theta))
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        Some(self.harmonics(|theta| breakpoints::dtc_warp(theta, duty_cycle)))
+    }
+}
+impl<F, const K: usize> WaveformExt for Bezier<F, K>
+{
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::{error::Error, f64::consts::PI};
+
+    use crate::{
+        waveform::{Triangle, Waveform},
+        Wavetable
+    };
+
+    use super::{Bezier, BezierSegment, Continuity};
+
+    fn assert_close<const N: usize>(a: Wavetable<f64, N>, b: Wavetable<f64, N>, tolerance: f64)
+    {
+        let (a, b) = (a.view(), b.view());
+        assert!((a.a0 - b.a0).abs() < tolerance);
+        for (a, b) in a.ab.iter().zip(b.ab)
+        {
+            assert!((a.0 - b.0).abs() < tolerance && (a.1 - b.1).abs() < tolerance);
+        }
+    }
+
+    fn curve() -> Bezier<f64, 3>
+    {
+        Bezier::new([
+            BezierSegment::cubic(0.0, [-1.0, 0.5, 1.5, 1.0]),
+            BezierSegment::quadratic(2.0, [1.0, 0.0, 0.2]),
+            BezierSegment::polynomial(4.5, [0.2, -1.0, 0.3, -0.4])
+        ], Continuity::None)
+    }
+
+    #[test]
+    fn it_works() -> Result<(), Box<dyn Error>>
+    {
+        crate::tests::print_waveform(Bezier::new([
+            BezierSegment::cubic(0.0, [-1.0, 0.5, 1.5, 1.0]),
+            BezierSegment::quadratic(2.0, [1.0, 0.0, 0.2]),
+            BezierSegment::polynomial(4.5, [0.2, -1.0, 0.3, -0.4])
+        ], Continuity::C1))
+    }
+
+    #[test]
+    fn matches_sampled()
+    {
+        let triangle = Bezier::new([
+            BezierSegment::linear(0.0, -1.0, 1.0),
+            BezierSegment::linear(PI, 1.0, -1.0)
+        ], Continuity::C0);
+        assert_close::<16>(triangle.wavetable().unwrap(), Triangle.wavetable().unwrap(), 1e-9);
+        assert_close::<16>(triangle.wavetable_with_dtc(0.3).unwrap(), Triangle.wavetable_with_dtc(0.3).unwrap(), 1e-9);
+
+        let curve = curve();
+        for duty_cycle in [None, Some(0.2)]
+        {
+            let exact = match duty_cycle
+            {
+                Some(duty_cycle) => curve.wavetable_with_dtc::<16>(duty_cycle),
+                None => curve.wavetable::<16>()
+            }.unwrap();
+            assert_close(exact, Wavetable::from_waveform::<_, 8192>(&curve, duty_cycle), 1e-3);
+        }
+    }
+
+    #[test]
+    fn continuity()
+    {
+        let [s0, s1, s2] = *curve().segments();
+        let mut curve = Bezier::new([s0, s1, s2], Continuity::C1);
+
+        let slopes = |curve: &Bezier<f64, 3>| {
+            let eps = 1e-6;
+            curve.segments().map(|segment| {
+                let theta = segment.theta;
+                let (left, right) = (curve.waveform(theta - eps), curve.waveform(theta + eps));
+                assert!((left - right).abs() < 1e-4);
+                (curve.waveform(theta - 2.0*eps) - left, right - curve.waveform(theta + 2.0*eps))
+            })
+        };
+        for (left, right) in slopes(&curve)
+        {
+            assert!((left - right).abs() < 1e-9);
+        }
+
+        curve.set_segment(1, [0.5, 0.5, 0.0, 0.0]);
+        assert_eq!(curve.segments()[1].y, [0.5, 0.5, 0.0, 0.0]);
+        for (left, right) in slopes(&curve)
+        {
+            assert!((left - right).abs() < 1e-9);
+        }
+
+        // Deserializing joins the segments again, which can move the control values by a rounding error.
+        let json = serde_json::to_string(&curve).unwrap();
+        let loaded = serde_json::from_str::<Bezier<f64, 3>>(&json).unwrap();
+        assert_eq!(loaded, Bezier::new(*curve.segments(), Continuity::C1));
+        for (a, b) in loaded.segments().iter().zip(curve.segments())
+        {
+            assert_eq!(a.theta, b.theta);
+            assert!(a.y.iter().zip(b.y).all(|(a, b)| (a - b).abs() < 1e-12));
+        }
+    }
+
+    #[test]
+    fn deserialize_joins()
+    {
+        let json = r#"{
+            "segments": [{"theta": 4.0, "y": [1.0, 1.0, 0.0, 0.0]}, {"theta": -6.0, "y": [0.0, 0.5, 0.5, -1.0]}],
+            "continuity": "C0"
+        }"#;
+        let curve = serde_json::from_str::<Bezier<f64, 2>>(json).unwrap();
+        assert_eq!(curve, Bezier::new([
+            BezierSegment::cubic(4.0, [1.0, 1.0, 0.0, 0.0]),
+            BezierSegment::cubic(-6.0, [0.0, 0.5, 0.5, -1.0])
+        ], Continuity::C0));
+        let [s0, s1] = *curve.segments();
+        assert!(s0.theta < s1.theta);
+        assert_eq!(s0.y[3], s1.y[0]);
+        assert_eq!(s1.y[3], s0.y[0]);
+    }
+}
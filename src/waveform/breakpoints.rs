@@ -0,0 +1,330 @@
+use num_traits::{Euclid, Float, FloatConst};
+
+use crate::Wavetable;
+
+use super::{Waveform, WaveformExt};
+
+/// A corner of a [`Breakpoints`] waveform.
+///
+/// The segment arriving at the breakpoint ends at `left`, and the one leaving it starts at `right`. If they differ, the waveform
+/// jumps there.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+pub struct Breakpoint<F>
+{
+    /// Phase, in radians.
+    pub theta: F,
+    pub left: F,
+    pub right: F
+}
+
+impl<F> Breakpoint<F>
+where
+    F: Copy
+{
+    pub const fn new(theta: F, value: F) -> Self
+    {
+        Self {
+            theta,
+            left: value,
+            right: value
+        }
+    }
+
+    pub const fn jump(theta: F, left: F, right: F) -> Self
+    {
+        Self {
+            theta,
+            left,
+            right
+        }
+    }
+}
+
+/// A piecewise-linear waveform drawn through `K` breakpoints, with optional jumps.
+///
+/// Since each segment is linear, its wavetable is exact. With a duty cycle, the phases are warped so that the first half of the
+/// period is squeezed into the duty cycle, the same way as for [`Triangle`](super::Triangle) and [`Sawtooth`](super::Sawtooth).
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+#[serde(
+    bound(serialize = "F: serde::Serialize", deserialize = "F: Float + FloatConst + Euclid + serde::Deserialize<'de>"),
+    from = "BreakpointsSerde<F, K>"
+)]
+pub struct Breakpoints<F, const K: usize>
+{
+    #[serde(with = "crate::util::serde_array")]
+    points: [Breakpoint<F>; K]
+}
+
+/// The breakpoints as they're stored, before [`Breakpoints::new`] wraps and sorts them.
+#[derive(serde::Deserialize)]
+#[serde(bound(deserialize = "F: serde::Deserialize<'de>"))]
+struct BreakpointsSerde<F, const K: usize>
+{
+    #[serde(with = "crate::util::serde_array")]
+    points: [Breakpoint<F>; K]
+}
+
+impl<F, const K: usize> From<BreakpointsSerde<F, K>> for Breakpoints<F, K>
+where
+    F: Float + FloatConst + Euclid
+{
+    fn from(value: BreakpointsSerde<F, K>) -> Self
+    {
+        Self::new(value.points)
+    }
+}
+
+impl<F, const K: usize> Breakpoints<F, K>
+where
+    F: Float + FloatConst + Euclid
+{
+    /// Wraps the phases of the breakpoints into `[0, 2π)`, and sorts them by phase. Breakpoints with the same phase keep their order.
+    pub fn new(mut points: [Breakpoint<F>; K]) -> Self
+    {
+        let tau = F::TAU();
+        for point in points.iter_mut()
+        {
+            point.theta = point.theta.rem_euclid(&tau);
+        }
+        for i in 1..K
+        {
+            let mut j = i;
+            while j > 0 && points[j - 1].theta > points[j].theta
+            {
+                points.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+        Self {
+            points
+        }
+    }
+
+    pub fn points(&self) -> &[Breakpoint<F>; K]
+    {
+        &self.points
+    }
+
+    /// The `i`-th segment, as its start and end `(theta, value)`. The last one wraps around to the first breakpoint.
+    fn segment(&self, i: usize) -> ((F, F), (F, F))
+    {
+        let start = &self.points[i];
+        let (end, wrap) = match self.points.get(i + 1)
+        {
+            Some(end) => (end, F::zero()),
+            None => (&self.points[0], F::TAU())
+        };
+        ((start.theta, start.right), (end.theta + wrap, end.left))
+    }
+
+    fn harmonics<const N: usize>(&self, warp: impl Fn(F) -> F) -> Wavetable<F, N>
+    {
+        let zero = F::zero();
+        let pi = F::PI();
+
+        let mut a0 = zero;
+        let mut ab = [(zero, zero); N];
+        let mut add = |(t0, y0): (F, F), (t1, y1): (F, F)| {
+            let (t0, t1) = (warp(t0), warp(t1));
+            let dt = t1 - t0;
+            if dt.is_nan() || dt <= zero
+            {
+                return
+            }
+            a0 = a0 + (y0 + y1)*dt;
+
+            let m = (y1 - y0)/dt;
+            for (k, (a, b)) in ab.iter_mut().enumerate()
+            {
+                let n = F::from(k + 1).unwrap();
+                let (s0, c0) = (n*t0).sin_cos();
+                let (s1, c1) = (n*t1).sin_cos();
+                *a = *a + (y1*s1 - y0*s0)/n + m*(c1 - c0)/(n*n);
+                *b = *b + (y0*c0 - y1*c1)/n + m*(s1 - s0)/(n*n);
+            }
+        };
+
+        for i in 0..K
+        {
+            let ((t0, y0), (t1, y1)) = self.segment(i);
+
+            // Split where the warp bends, so each piece stays linear.
+            let mut start = (t0, y0);
+            for cut in [pi, pi + pi, pi + pi + pi]
+            {
+                if t0 < cut && cut < t1
+                {
+                    let y = y0 + (y1 - y0)*(cut - t0)/(t1 - t0);
+                    add(start, (cut, y));
+                    start = (cut, y);
+                }
+            }
+            add(start, (t1, y1));
+        }
+
+        Wavetable::from_array(a0/(F::TAU() + F::TAU()), ab.map(|(a, b)| (a/pi, b/pi)))
+    }
+}
+
+impl<F, const K: usize> Waveform<F> for Breakpoints<F, K>
+where
+    F: Float + FloatConst + Euclid
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        let tau = F::TAU();
+
+        let theta = theta.rem_euclid(&tau);
+        let (i, theta) = match self.points.iter().rposition(|point| point.theta <= theta)
+        {
+            Some(i) => (i, theta),
+            None if K == 0 => return F::zero(),
+            None => (K - 1, theta + tau)
+        };
+
+        let ((t0, y0), (t1, y1)) = self.segment(i);
+        if t1 > t0
+        {
+            y0 + (y1 - y0)*(theta - t0)/(t1 - t0)
+        }
+        else
+        {
+            y0
+        }
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        self.waveform(dtc_unwarp(theta, duty_cycle))
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        Some(self.harmonics(|theta| theta))
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        Some(self.harmonics(|theta| dtc_warp(theta, duty_cycle)))
+    }
+}
+impl<F, const K: usize> WaveformExt for Breakpoints<F, K>
+{
+}
+
+/// Moves phase `π` to the duty cycle, stretching the two halves of each period linearly around it.
+pub(super) fn dtc_warp<F>(theta: F, duty_cycle: F) -> F
+where
+    F: Float + FloatConst
+{
+    let zero = F::zero();
+    let one = F::one();
+    let pi = F::PI();
+    let tau = F::TAU();
+
+    let d = tau*duty_cycle.clamp(zero, one);
+    let k = (theta/tau).floor()*tau;
+    let theta = theta - k;
+    k + if theta < pi
+    {
+        theta*d/pi
+    }
+    else
+    {
+        d + (theta - pi)*(tau - d)/pi
+    }
+}
+
+/// The inverse of [`dtc_warp`], wrapped into `[0, 2π)`.
+pub(super) fn dtc_unwarp<F>(theta: F, duty_cycle: F) -> F
+where
+    F: Float + FloatConst + Euclid
+{
+    let zero = F::zero();
+    let one = F::one();
+    let pi = F::PI();
+    let tau = F::TAU();
+
+    let d = tau*duty_cycle.clamp(zero, one);
+    let theta = theta.rem_euclid(&tau);
+    if theta < d
+    {
+        theta*pi/d
+    }
+    else
+    {
+        pi + (theta - d)*pi/(tau - d)
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::{error::Error, f64::consts::PI};
+
+    use crate::{
+        waveform::{Sawtooth, Triangle, Waveform},
+        Wavetable
+    };
+
+    use super::{Breakpoint, Breakpoints};
+
+    fn assert_close<const N: usize>(a: Wavetable<f64, N>, b: Wavetable<f64, N>)
+    {
+        let (a, b) = (a.view(), b.view());
+        assert!((a.a0 - b.a0).abs() < 1e-9);
+        for (a, b) in a.ab.iter().zip(b.ab)
+        {
+            assert!((a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn it_works() -> Result<(), Box<dyn Error>>
+    {
+        crate::tests::print_waveform(Breakpoints::new([
+            Breakpoint::new(0.0, 0.0),
+            Breakpoint::new(1.0, 1.0),
+            Breakpoint::jump(2.0, -0.5, 0.5),
+            Breakpoint::new(4.0, -1.0)
+        ]))
+    }
+
+    #[test]
+    fn matches_analytic()
+    {
+        let triangle = Breakpoints::new([
+            Breakpoint::new(PI, 1.0),
+            Breakpoint::new(0.0, -1.0)
+        ]);
+        let sawtooth = Breakpoints::new([
+            Breakpoint::jump(0.0, 1.0, -1.0),
+            Breakpoint::jump(PI, 1.0, -1.0)
+        ]);
+
+        assert_close::<16>(triangle.wavetable().unwrap(), Triangle.wavetable().unwrap());
+        assert_close::<16>(sawtooth.wavetable().unwrap(), Sawtooth.wavetable().unwrap());
+        for duty_cycle in [0.1, 0.3, 0.8]
+        {
+            assert_close::<16>(triangle.wavetable_with_dtc(duty_cycle).unwrap(), Triangle.wavetable_with_dtc(duty_cycle).unwrap());
+            assert_close::<16>(sawtooth.wavetable_with_dtc(duty_cycle).unwrap(), Sawtooth.wavetable_with_dtc(duty_cycle).unwrap());
+
+            for k in 0..64
+            {
+                let theta = 0.1 + k as f64*0.2;
+                assert!((triangle.waveform_with_dtc(theta, duty_cycle) - Triangle.waveform_with_dtc(theta, duty_cycle)).abs() < 1e-9);
+                assert!((sawtooth.waveform_with_dtc(theta, duty_cycle) - Sawtooth.waveform_with_dtc(theta, duty_cycle)).abs() < 1e-9);
+            }
+        }
+
+        let json = serde_json::to_string(&sawtooth).unwrap();
+        assert_eq!(serde_json::from_str::<Breakpoints<f64, 2>>(&json).unwrap(), sawtooth);
+    }
+
+    #[test]
+    fn deserialize_sorts()
+    {
+        let json = r#"{"points": [{"theta": 4.0, "left": -1.0, "right": -1.0}, {"theta": -6.0, "left": 1.0, "right": 1.0}]}"#;
+        let breakpoints = serde_json::from_str::<Breakpoints<f64, 2>>(json).unwrap();
+        assert_eq!(breakpoints, Breakpoints::new([Breakpoint::new(4.0, -1.0), Breakpoint::new(-6.0, 1.0)]));
+        assert!(breakpoints.points()[0].theta < breakpoints.points()[1].theta);
+    }
+}
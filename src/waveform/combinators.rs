@@ -0,0 +1,475 @@
+use num_traits::Float;
+
+use crate::Wavetable;
+
+use super::Waveform;
+
+/// Builder methods for combining waveforms.
+///
+/// The combinators compute their wavetables exactly from the wavetables of the waveforms they combine, so they stay
+/// band-limited in [`Wave`](crate::oscillator::Wave) as long as those do.
+///
+/// Each waveform of this crate implements it next to its [`Waveform`] implementation, and so do groups made with
+/// [`waveforms!`](crate::waveforms). Implement it for your own waveforms to combine them the same way, since all of its methods are
+/// provided. It isn't implemented for every [`Waveform`] at once, since most waveforms are waveforms of every float type,
+/// and the float type would then have to be spelled out on every call.
+pub trait WaveformExt: Sized
+{
+    fn mix<W>(self, other: W) -> Mix<Self, W>
+    {
+        Mix {
+            a: self,
+            b: other
+        }
+    }
+    fn gain<F>(self, gain: F) -> Gain<F, Self>
+    {
+        Gain {
+            waveform: self,
+            gain
+        }
+    }
+    fn offset<F>(self, offset: F) -> Offset<F, Self>
+    {
+        Offset {
+            waveform: self,
+            offset
+        }
+    }
+    fn invert(self) -> Invert<Self>
+    {
+        Invert(self)
+    }
+    /// Shifts the waveform earlier by `phase` radians.
+    fn phase_shift<F>(self, phase: F) -> PhaseShift<F, Self>
+    {
+        PhaseShift {
+            waveform: self,
+            phase
+        }
+    }
+    fn reverse(self) -> Reverse<Self>
+    {
+        Reverse(self)
+    }
+    /// Multiplies by another waveform, played `ratio` times as fast.
+    fn ring<W>(self, modulator: W, ratio: u32) -> RingMod<Self, W>
+    {
+        RingMod {
+            carrier: self,
+            modulator,
+            ratio
+        }
+    }
+    /// Modulates the amplitude by another waveform, played `ratio` times as fast.
+    fn am<F, W>(self, modulator: W, ratio: u32, depth: F) -> Am<F, Self, W>
+    {
+        Am {
+            carrier: self,
+            modulator,
+            ratio,
+            depth
+        }
+    }
+}
+
+/// The sum of two waveforms.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Mix<A, B>
+{
+    pub a: A,
+    pub b: B
+}
+
+impl<A, B> Mix<A, B>
+{
+    fn mix_wavetables<F, const N: usize>(a: Option<Wavetable<F, N>>, b: Option<Wavetable<F, N>>) -> Option<Wavetable<F, N>>
+    where
+        F: Float
+    {
+        Some(a? + b?)
+    }
+}
+
+impl<F, A, B> Waveform<F> for Mix<A, B>
+where
+    F: Float,
+    A: Waveform<F>,
+    B: Waveform<F>
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        self.a.waveform(theta) + self.b.waveform(theta)
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        self.a.waveform_with_dtc(theta, duty_cycle) + self.b.waveform_with_dtc(theta, duty_cycle)
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        Self::mix_wavetables(self.a.wavetable(), self.b.wavetable())
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        Self::mix_wavetables(self.a.wavetable_with_dtc(duty_cycle), self.b.wavetable_with_dtc(duty_cycle))
+    }
+}
+impl<A, B> WaveformExt for Mix<A, B>
+{
+}
+
+/// A waveform scaled by a gain.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Gain<F, W>
+{
+    pub waveform: W,
+    pub gain: F
+}
+
+impl<F, W> Waveform<F> for Gain<F, W>
+where
+    F: Float,
+    W: Waveform<F>
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        self.gain*self.waveform.waveform(theta)
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        self.gain*self.waveform.waveform_with_dtc(theta, duty_cycle)
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        self.waveform.wavetable().map(|w| w*self.gain)
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        self.waveform.wavetable_with_dtc(duty_cycle).map(|w| w*self.gain)
+    }
+}
+impl<F, W> WaveformExt for Gain<F, W>
+{
+}
+
+/// A waveform with a DC-offset added.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Offset<F, W>
+{
+    pub waveform: W,
+    pub offset: F
+}
+
+impl<F, W> Offset<F, W>
+where
+    F: Float
+{
+    fn offset_wavetable<const N: usize>(&self, mut wavetable: Wavetable<F, N>) -> Wavetable<F, N>
+    {
+        *wavetable.a0_mut() = wavetable.a0() + self.offset;
+        wavetable
+    }
+}
+
+impl<F, W> Waveform<F> for Offset<F, W>
+where
+    F: Float,
+    W: Waveform<F>
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        self.waveform.waveform(theta) + self.offset
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        self.waveform.waveform_with_dtc(theta, duty_cycle) + self.offset
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        self.waveform.wavetable().map(|w| self.offset_wavetable(w))
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        self.waveform.wavetable_with_dtc(duty_cycle).map(|w| self.offset_wavetable(w))
+    }
+}
+impl<F, W> WaveformExt for Offset<F, W>
+{
+}
+
+/// A waveform upside down.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Invert<W>(pub W);
+
+impl<F, W> Waveform<F> for Invert<W>
+where
+    F: Float,
+    W: Waveform<F>
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        -self.0.waveform(theta)
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        -self.0.waveform_with_dtc(theta, duty_cycle)
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        self.0.wavetable().map(|w| -w)
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        self.0.wavetable_with_dtc(duty_cycle).map(|w| -w)
+    }
+}
+impl<W> WaveformExt for Invert<W>
+{
+}
+
+/// A waveform shifted earlier in phase, so that it's at `theta + phase` when the phase is `theta`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+pub struct PhaseShift<F, W>
+{
+    pub waveform: W,
+    /// Phase, in radians.
+    pub phase: F
+}
+
+impl<F, W> Waveform<F> for PhaseShift<F, W>
+where
+    F: Float,
+    W: Waveform<F>
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        self.waveform.waveform(theta + self.phase)
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        self.waveform.waveform_with_dtc(theta + self.phase, duty_cycle)
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        self.waveform.wavetable().map(|w| w.phase_shift(self.phase))
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        self.waveform.wavetable_with_dtc(duty_cycle).map(|w| w.phase_shift(self.phase))
+    }
+}
+impl<F, W> WaveformExt for PhaseShift<F, W>
+{
+}
+
+/// A waveform played backwards.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Reverse<W>(pub W);
+
+impl<F, W> Waveform<F> for Reverse<W>
+where
+    F: Float,
+    W: Waveform<F>
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        self.0.waveform(-theta)
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        self.0.waveform_with_dtc(-theta, duty_cycle)
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        self.0.wavetable().map(|w| w.map_harmonics(|_, (a, b)| (a, -b)))
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        self.0.wavetable_with_dtc(duty_cycle).map(|w| w.map_harmonics(|_, (a, b)| (a, -b)))
+    }
+}
+impl<W> WaveformExt for Reverse<W>
+{
+}
+
+/// Ring modulation, the product of a carrier and a modulator at `ratio` times its frequency.
+///
+/// The wavetable is the convolution of their wavetables, see [`Wavetable::product`]. The duty cycle goes to both.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+pub struct RingMod<A, B>
+{
+    pub carrier: A,
+    pub modulator: B,
+    pub ratio: u32
+}
+
+impl<F, A, B> Waveform<F> for RingMod<A, B>
+where
+    F: Float,
+    A: Waveform<F>,
+    B: Waveform<F>
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        let ratio = F::from(self.ratio).unwrap();
+        self.carrier.waveform(theta)*self.modulator.waveform(ratio*theta)
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        let ratio = F::from(self.ratio).unwrap();
+        self.carrier.waveform_with_dtc(theta, duty_cycle)*self.modulator.waveform_with_dtc(ratio*theta, duty_cycle)
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        let modulator: Wavetable<F, N> = self.modulator.wavetable()?;
+        Some(self.carrier.wavetable()?.product(&modulator.at_ratio(self.ratio as usize)))
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        let modulator: Wavetable<F, N> = self.modulator.wavetable_with_dtc(duty_cycle)?;
+        Some(self.carrier.wavetable_with_dtc(duty_cycle)?.product(&modulator.at_ratio(self.ratio as usize)))
+    }
+}
+impl<A, B> WaveformExt for RingMod<A, B>
+{
+}
+
+/// Amplitude modulation, `carrier·(1 + depth·modulator)`, with the modulator at `ratio` times the frequency of the carrier.
+///
+/// The wavetable is exact like for [`RingMod`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Am<F, A, B>
+{
+    pub carrier: A,
+    pub modulator: B,
+    pub ratio: u32,
+    pub depth: F
+}
+
+impl<F, A, B> Am<F, A, B>
+where
+    F: Float
+{
+    fn envelope<const N: usize>(&self, modulator: Wavetable<F, N>) -> Wavetable<F, N>
+    {
+        let mut envelope = modulator.at_ratio(self.ratio as usize)*self.depth;
+        *envelope.a0_mut() = envelope.a0() + F::one();
+        envelope
+    }
+}
+
+impl<F, A, B> Waveform<F> for Am<F, A, B>
+where
+    F: Float,
+    A: Waveform<F>,
+    B: Waveform<F>
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        let ratio = F::from(self.ratio).unwrap();
+        self.carrier.waveform(theta)*(F::one() + self.depth*self.modulator.waveform(ratio*theta))
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        let ratio = F::from(self.ratio).unwrap();
+        self.carrier.waveform_with_dtc(theta, duty_cycle)*(F::one() + self.depth*self.modulator.waveform_with_dtc(ratio*theta, duty_cycle))
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        Some(self.carrier.wavetable()?.product(&self.envelope(self.modulator.wavetable()?)))
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        Some(self.carrier.wavetable_with_dtc(duty_cycle)?.product(&self.envelope(self.modulator.wavetable_with_dtc(duty_cycle)?)))
+    }
+}
+impl<F, A, B> WaveformExt for Am<F, A, B>
+{
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::error::Error;
+
+    use crate::{
+        waveform::{Sawtooth, Sine, Square, Triangle, Waveform},
+        Wavetable
+    };
+
+    use super::WaveformExt;
+
+    #[test]
+    fn it_works() -> Result<(), Box<dyn Error>>
+    {
+        crate::tests::print_waveform(Sawtooth.mix(Triangle.gain(0.5f32)).phase_shift(1.0).reverse())
+    }
+
+    #[test]
+    fn exact_wavetables()
+    {
+        let waveform = Sawtooth.mix(Triangle.gain(0.5))
+            .phase_shift(1.0)
+            .reverse()
+            .mix(Square.invert())
+            .offset(0.25);
+
+        for k in 0..16
+        {
+            let theta = 0.1 + k as f64*0.4;
+            let y = Sawtooth.waveform(1.0 - theta) + 0.5*Triangle.waveform(1.0 - theta) - Square.waveform(theta) + 0.25;
+            assert!((waveform.waveform(theta) - y).abs() < 1e-9);
+        }
+
+        for duty_cycle in [None, Some(0.3)]
+        {
+            let exact: Wavetable<f64, 16> = match duty_cycle
+            {
+                Some(duty_cycle) => waveform.wavetable_with_dtc(duty_cycle),
+                None => waveform.wavetable()
+            }.unwrap();
+            let sampled: Wavetable<f64, 16> = Wavetable::from_waveform::<_, 8192>(&waveform, duty_cycle);
+            let (exact, sampled) = (exact.view(), sampled.view());
+            assert!((exact.a0 - sampled.a0).abs() < 1e-2);
+            for (exact, sampled) in exact.ab.iter().zip(sampled.ab)
+            {
+                assert!((exact.0 - sampled.0).abs() < 1e-2 && (exact.1 - sampled.1).abs() < 1e-2);
+            }
+        }
+    }
+
+    #[test]
+    fn modulation()
+    {
+        let a = Wavetable::<f64, 4>::from_fn(0.25, |m| (1.0/(m + 1) as f64, 0.5 - m as f64*0.2));
+        let b = Wavetable::<f64, 4>::from_fn(-0.5, |m| (0.3*m as f64, 1.0/(m + 2) as f64));
+
+        let ring = a.ring(b, 2);
+        let am = a.am(b, 3, 0.5);
+        let ring_table: Wavetable<f64, 16> = ring.wavetable().unwrap();
+        let am_table: Wavetable<f64, 16> = am.wavetable().unwrap();
+        for k in 0..32
+        {
+            let theta = 0.1 + k as f64*0.2;
+            assert!((ring_table.waveform(theta, 16).unwrap() - ring.waveform(theta)).abs() < 1e-9);
+            assert!((am_table.waveform(theta, 16).unwrap() - am.waveform(theta)).abs() < 1e-9);
+        }
+
+        // Sines at 1 and 3 give sum and difference tones at 4 and 2.
+        let bell: Wavetable<f64, 8> = Sine.ring(Sine, 3).wavetable().unwrap();
+        assert_eq!(bell.a0(), 0.0);
+        for n in 1..=8
+        {
+            let a = if n == 2 || n == 4 {0.5} else {0.0};
+            assert!((bell.harmonic(n).unwrap().0 - a).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use num_traits::Float;
+
+use crate::Wavetable;
+
+use super::{Waveform, WaveformExt};
+
+/// A waveform made from closures, for shapes that don't need a type of their own.
+///
+/// The waveform `W` is `Fn(theta) -> F`. Optionally, it can be given a duty cycle version `D`, as `Fn(theta, duty_cycle) -> F`, and
+/// an analytic wavetable `H`, as `Fn(n, duty_cycle) -> (a, b)`, giving the harmonic coefficients for `n > 0`, and the DC-offset as
+/// `a` for `n = 0`. Without a duty cycle version, the duty cycle is ignored. Without a wavetable, it can't be band-limited, unless
+/// wrapped in a [`Sampled`](super::Sampled).
+///
+/// Give it a name with a type alias, like `type Bump = FnWaveform<fn(f32) -> f32>`, to use it in [`waveforms!`](crate::waveforms).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FnWaveform<W, D = (), H = ()>
+{
+    pub waveform: W,
+    pub waveform_with_dtc: D,
+    pub harmonics: H
+}
+
+impl<W> FnWaveform<W>
+{
+    pub const fn new(waveform: W) -> Self
+    {
+        Self {
+            waveform,
+            waveform_with_dtc: (),
+            harmonics: ()
+        }
+    }
+}
+
+impl<W, D, H> FnWaveform<W, D, H>
+{
+    pub fn with_dtc<DD>(self, waveform_with_dtc: DD) -> FnWaveform<W, DD, H>
+    {
+        let Self { waveform, harmonics, .. } = self;
+        FnWaveform {
+            waveform,
+            waveform_with_dtc,
+            harmonics
+        }
+    }
+
+    pub fn with_harmonics<HH>(self, harmonics: HH) -> FnWaveform<W, D, HH>
+    {
+        let Self { waveform, waveform_with_dtc, .. } = self;
+        FnWaveform {
+            waveform,
+            waveform_with_dtc,
+            harmonics
+        }
+    }
+}
+
+impl<W> From<W> for FnWaveform<W>
+{
+    fn from(waveform: W) -> Self
+    {
+        Self::new(waveform)
+    }
+}
+
+/// The optional duty cycle version of a [`FnWaveform`]. Either `()` or `Fn(theta, duty_cycle) -> F`.
+pub trait DtcFn<F>
+{
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> Option<F>;
+}
+impl<F> DtcFn<F> for ()
+{
+    fn waveform_with_dtc(&self, _theta: F, _duty_cycle: F) -> Option<F>
+    {
+        None
+    }
+}
+impl<F, D> DtcFn<F> for D
+where
+    D: Fn(F, F) -> F
+{
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> Option<F>
+    {
+        Some(self(theta, duty_cycle))
+    }
+}
+
+/// The optional analytic wavetable of a [`FnWaveform`]. Either `()` or `Fn(n, duty_cycle) -> (a, b)`.
+pub trait HarmonicsFn<F>
+where
+    F: Float
+{
+    fn wavetable<const N: usize>(&self, duty_cycle: Option<F>) -> Option<Wavetable<F, N>>;
+}
+impl<F> HarmonicsFn<F> for ()
+where
+    F: Float
+{
+    fn wavetable<const N: usize>(&self, _duty_cycle: Option<F>) -> Option<Wavetable<F, N>>
+    {
+        None
+    }
+}
+impl<F, H> HarmonicsFn<F> for H
+where
+    F: Float,
+    H: Fn(usize, Option<F>) -> (F, F)
+{
+    fn wavetable<const N: usize>(&self, duty_cycle: Option<F>) -> Option<Wavetable<F, N>>
+    {
+        Some(Wavetable::from_fn(self(0, duty_cycle).0, |m| self(m + 1, duty_cycle)))
+    }
+}
+
+impl<F, W, D, H> Waveform<F> for FnWaveform<W, D, H>
+where
+    F: Float,
+    W: Fn(F) -> F,
+    D: DtcFn<F>,
+    H: HarmonicsFn<F>
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        (self.waveform)(theta)
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        self.waveform_with_dtc.waveform_with_dtc(theta, duty_cycle)
+            .unwrap_or_else(|| self.waveform(theta))
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        self.harmonics.wavetable(None)
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        self.harmonics.wavetable(Some(duty_cycle))
+    }
+}
+impl<W, D, H> WaveformExt for FnWaveform<W, D, H>
+{
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f32::consts::TAU;
+
+    use crate::{
+        oscillator::{Direct, Oscillator, OscillatorState, Wave},
+        waveform::Waveform
+    };
+
+    use super::FnWaveform;
+
+    #[test]
+    fn it_works()
+    {
+        const RATE: f32 = 8000.0;
+
+        let bump = FnWaveform::new(|theta: f32| theta.cos().max(0.0));
+        assert_eq!(bump.waveform(0.0), 1.0);
+        assert_eq!(bump.waveform(TAU/2.0), 0.0);
+        assert!(bump.wavetable::<8>().is_none());
+
+        let pulse = FnWaveform::new(|theta: f32| theta.cos())
+            .with_dtc(|theta: f32, duty_cycle: f32| if theta.rem_euclid(TAU) < TAU*duty_cycle {1.0} else {-1.0})
+            .with_harmonics(|n, _| if n == 1 {(1.0, 0.0)} else {(0.0, 0.0)});
+        assert_eq!(pulse.waveform_with_dtc(1.0, 0.25), 1.0);
+        assert_eq!(pulse.waveform_with_dtc(2.0, 0.25), -1.0);
+
+        let mut direct = Oscillator::new(TAU*100.0, 0.0, Direct::from(bump));
+        let mut wave = Oscillator::new(TAU*1000.0, 0.0, Wave::<_, _, 8>::from(pulse));
+        for _ in 0..100
+        {
+            assert!(direct.next(RATE).is_finite());
+            let y = wave.next(RATE);
+            assert!((y - wave.theta().cos()).abs() < 1e-4);
+        }
+        assert!(wave.wavetable().is_some());
+    }
+}
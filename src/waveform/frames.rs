@@ -0,0 +1,119 @@
+use core::ops::{Mul, Sub};
+
+use num_complex::Complex;
+use num_traits::{Float, FloatConst, Zero};
+
+use crate::Wavetable;
+
+use super::{Waveform, WaveformExt};
+
+/// `K` wavetable frames, scanned through by a continuous `position`, like the wavetables of a Serum-style synth.
+///
+/// Between two frames, their harmonics are interpolated linearly, which is the same as crossfading between their outputs. So moving
+/// the position doesn't rebuild anything.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+#[serde(bound(serialize = "F: serde::Serialize", deserialize = "F: serde::Deserialize<'de>"))]
+pub struct Frames<F, const K: usize, const N: usize>
+where
+    F: Float
+{
+    #[serde(with = "crate::util::serde_array")]
+    frames: [Wavetable<F, N>; K],
+    /// Position between the frames, from `0` at the first frame to `K - 1` at the last.
+    pub position: F
+}
+
+impl<F, const K: usize, const N: usize> Frames<F, K, N>
+where
+    F: Float
+{
+    pub fn new(frames: [Wavetable<F, N>; K], position: F) -> Self
+    {
+        Self {
+            frames,
+            position
+        }
+    }
+
+    pub fn frames(&self) -> &[Wavetable<F, N>; K]
+    {
+        &self.frames
+    }
+    pub fn frames_mut(&mut self) -> &mut [Wavetable<F, N>; K]
+    {
+        &mut self.frames
+    }
+
+    /// The two frames around the position, and how far the position is from the first towards the second.
+    fn adjacent(&self) -> Option<(&Wavetable<F, N>, &Wavetable<F, N>, F)>
+    {
+        let last = K.checked_sub(1)?;
+        let position = self.position.max(F::zero()).min(F::from(last).unwrap());
+        let i = position.floor().to_usize().unwrap_or(0).min(last.saturating_sub(1));
+        let frac = position - F::from(i).unwrap();
+        Some((&self.frames[i], &self.frames[(i + 1).min(last)], frac))
+    }
+
+    /// Evaluates the frames around the position, summing only the first `up_to` harmonics, and crossfades between them.
+    pub fn waveform_up_to(&self, theta: F, up_to: usize) -> Option<F>
+    {
+        self.lerp(|w| w.waveform(theta, up_to))
+    }
+    /// Like [`Frames::waveform_up_to`], but the [analytic](Wavetable::analytic) output.
+    pub fn analytic_up_to(&self, theta: F, up_to: usize) -> Option<Complex<F>>
+    {
+        self.lerp(|w| w.analytic(theta, up_to))
+    }
+
+    fn lerp<T>(&self, waveform: impl Fn(&Wavetable<F, N>) -> Option<T>) -> Option<T>
+    where
+        T: Zero + Sub<Output = T> + Mul<F, Output = T> + Copy
+    {
+        let Some((w0, w1, frac)) = self.adjacent()
+        else
+        {
+            return Some(T::zero())
+        };
+        let y0 = waveform(w0)?;
+        if frac == F::zero()
+        {
+            return Some(y0)
+        }
+        let y1 = waveform(w1)?;
+        Some(y0 + (y1 - y0)*frac)
+    }
+
+    /// The wavetable at the position.
+    pub fn wavetable(&self) -> Wavetable<F, N>
+    {
+        let zero = F::zero();
+        let Some((w0, w1, frac)) = self.adjacent()
+        else
+        {
+            return Wavetable::from_array(zero, [(zero, zero); N])
+        };
+        let (w0, w1) = (w0.view(), w1.view());
+        let lerp = |y0: F, y1: F| y0 + (y1 - y0)*frac;
+        Wavetable::from_fn(lerp(*w0.a0, *w1.a0), |n| (lerp(w0.ab[n].0, w1.ab[n].0), lerp(w0.ab[n].1, w1.ab[n].1)))
+    }
+}
+
+impl<F, const K: usize, const N: usize> Waveform<F> for Frames<F, K, N>
+where
+    F: Float + FloatConst
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        self.waveform_up_to(theta, N).unwrap_or_else(F::zero)
+    }
+
+    fn wavetable<const M: usize>(&self) -> Option<Wavetable<F, M>>
+    {
+        Some(Frames::wavetable(self).resize())
+    }
+}
+impl<F, const K: usize, const N: usize> WaveformExt for Frames<F, K, N>
+where
+    F: Float
+{
+}
@@ -1,16 +1,29 @@
+use core::simd::{Simd, SimdElement};
+
 use num_traits::Float;
 
-use crate::Wavetable;
+use crate::{simd::FloatLanes, Wavetable};
 
 moddef::moddef!(
     flat(pub) mod {
         waveforms,
+        bezier,
+        breakpoints,
+        combinators,
+        fn_waveform,
+        frames,
+        morph,
         noise,
+        normalized,
+        phase_distortion,
+        pm_sine,
         rounded_triangle,
+        sampled,
         sawtooth,
         sine,
         square,
-        triangle
+        triangle,
+        vector
     }
 );
 
@@ -34,4 +47,159 @@ where
         let _ = duty_cycle;
         self.wavetable()
     }
-}
\ No newline at end of file
+}
+
+/// A [`Waveform`] that can be evaluated in `L` lanes at once.
+///
+/// Every waveform gets a lane-by-lane fallback. Waveforms with simple naive formulas override it with a vectorized one.
+pub trait SimdWaveform<F>: Waveform<F>
+where
+    F: Float + SimdElement
+{
+    fn waveform_simd<const L: usize>(&self, theta: Simd<F, L>) -> Simd<F, L>
+    where
+        Simd<F, L>: FloatLanes<F, L>;
+}
+
+impl<F, W> SimdWaveform<F> for W
+where
+    F: Float + SimdElement,
+    W: Waveform<F>
+{
+    default fn waveform_simd<const L: usize>(&self, theta: Simd<F, L>) -> Simd<F, L>
+    where
+        Simd<F, L>: FloatLanes<F, L>
+    {
+        Simd::from_array(theta.to_array().map(|theta| self.waveform(theta)))
+    }
+}
+
+/// Object-safe view of a [`Waveform`], so waveforms can be chosen at runtime behind `dyn`.
+///
+/// Wavetables are reached with [`wavetable_into`](DynWaveform::wavetable_into), so waveforms behind `dyn` are band-limited by
+/// [`Wave`](crate::oscillator::Wave) too.
+pub trait DynWaveform<F>
+where
+    F: Float
+{
+    fn waveform(&self, theta: F) -> F;
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F;
+
+    /// Writes the first `ab.len()` harmonics of the wavetable into `ab`, and returns the DC-offset, or `None` if there's no
+    /// wavetable.
+    ///
+    /// The harmonics are taken from a wavetable of the next power of two, up to [`DYN_WAVETABLE_MAX`] harmonics.
+    fn wavetable_into(&self, duty_cycle: Option<F>, ab: &mut [(F, F)]) -> Option<F>;
+}
+
+/// The most harmonics [`DynWaveform::wavetable_into`] can find.
+pub const DYN_WAVETABLE_MAX: usize = 4096;
+
+impl<F, W> DynWaveform<F> for W
+where
+    F: Float,
+    W: Waveform<F>
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        Waveform::waveform(self, theta)
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        Waveform::waveform_with_dtc(self, theta, duty_cycle)
+    }
+
+    fn wavetable_into(&self, duty_cycle: Option<F>, ab: &mut [(F, F)]) -> Option<F>
+    {
+        macro_rules! with_len {
+            ($($n:literal),*) => {
+                match ab.len()
+                {
+                    $(len if len <= $n => wavetable_into::<F, W, $n>(self, duty_cycle, ab),)*
+                    _ => None
+                }
+            };
+        }
+        with_len!(1, 2, 4, 8, 16, 32, 64, 128, 256, 512, 1024, 2048, 4096)
+    }
+}
+
+fn wavetable_into<F, W, const N: usize>(waveform: &W, duty_cycle: Option<F>, ab: &mut [(F, F)]) -> Option<F>
+where
+    F: Float,
+    W: Waveform<F>
+{
+    let wavetable: Wavetable<F, N> = match duty_cycle
+    {
+        Some(duty_cycle) => waveform.wavetable_with_dtc(duty_cycle),
+        None => waveform.wavetable()
+    }?;
+    let view = wavetable.view();
+    ab.copy_from_slice(view.ab.get(..ab.len())?);
+    Some(*view.a0)
+}
+
+fn dyn_wavetable<F, const N: usize>(waveform: &dyn DynWaveform<F>, duty_cycle: Option<F>) -> Option<Wavetable<F, N>>
+where
+    F: Float
+{
+    let zero = F::zero();
+    let mut ab = [(zero, zero); N];
+    let a0 = waveform.wavetable_into(duty_cycle, &mut ab)?;
+    Some(Wavetable::from_array(a0, ab))
+}
+
+impl<F> Waveform<F> for &dyn DynWaveform<F>
+where
+    F: Float
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        DynWaveform::waveform(*self, theta)
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        DynWaveform::waveform_with_dtc(*self, theta, duty_cycle)
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        dyn_wavetable(*self, None)
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        dyn_wavetable(*self, Some(duty_cycle))
+    }
+}
+impl<F> WaveformExt for &dyn DynWaveform<F>
+where
+    F: Float
+{
+}
+impl<F> Waveform<F> for &mut dyn DynWaveform<F>
+where
+    F: Float
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        DynWaveform::waveform(*self, theta)
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        DynWaveform::waveform_with_dtc(*self, theta, duty_cycle)
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        dyn_wavetable(*self, None)
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        dyn_wavetable(*self, Some(duty_cycle))
+    }
+}
+impl<F> WaveformExt for &mut dyn DynWaveform<F>
+where
+    F: Float
+{
+}
@@ -0,0 +1,163 @@
+use num_traits::{Euclid, Float, FloatConst};
+
+use crate::Wavetable;
+
+use super::{MekkaWaveform, Waveform, WaveformExt};
+
+/// Morphs between two waveforms through their wavetables, with [`Wavetable::morph`], instead of crossfading between their
+/// outputs.
+///
+/// Waveforms without a wavetable can't be morphed this way, and are crossfaded instead. Wrap them in a [`Sampled`](super::Sampled)
+/// to morph them spectrally too. Only a [`Wave`](crate::oscillator::Wave) morphs spectrally. Evaluated directly, the outputs are
+/// crossfaded, since rebuilding the morphed wavetable every sample would be too slow.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+pub struct Morph<F, A, B>
+{
+    pub a: A,
+    pub b: B,
+    /// From `0` at `a` to `1` at `b`.
+    pub morph: F
+}
+
+impl<F, A, B> Morph<F, A, B>
+where
+    F: Float + FloatConst + Euclid,
+    A: Waveform<F>,
+    B: Waveform<F>
+{
+    fn morph_wavetable<const N: usize>(&self, a: Option<Wavetable<F, N>>, b: impl FnOnce() -> Option<Wavetable<F, N>>) -> Option<Wavetable<F, N>>
+    {
+        if self.morph <= F::zero()
+        {
+            return a
+        }
+        let b = b()?;
+        if self.morph >= F::one()
+        {
+            return Some(b)
+        }
+        Some(a?.morph(&b, self.morph))
+    }
+
+    fn crossfade(&self, a: F, b: F) -> F
+    {
+        let t = self.morph.max(F::zero()).min(F::one());
+        a + (b - a)*t
+    }
+}
+
+impl<F, A, B> Waveform<F> for Morph<F, A, B>
+where
+    F: Float + FloatConst + Euclid,
+    A: Waveform<F>,
+    B: Waveform<F>
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        if self.morph <= F::zero()
+        {
+            return self.a.waveform(theta)
+        }
+        if self.morph >= F::one()
+        {
+            return self.b.waveform(theta)
+        }
+        self.crossfade(self.a.waveform(theta), self.b.waveform(theta))
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        if self.morph <= F::zero()
+        {
+            return self.a.waveform_with_dtc(theta, duty_cycle)
+        }
+        if self.morph >= F::one()
+        {
+            return self.b.waveform_with_dtc(theta, duty_cycle)
+        }
+        self.crossfade(self.a.waveform_with_dtc(theta, duty_cycle), self.b.waveform_with_dtc(theta, duty_cycle))
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        self.morph_wavetable(self.a.wavetable(), || self.b.wavetable())
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        self.morph_wavetable(self.a.wavetable_with_dtc(duty_cycle), || self.b.wavetable_with_dtc(duty_cycle))
+    }
+}
+impl<F, A, B> WaveformExt for Morph<F, A, B>
+{
+}
+
+impl MekkaWaveform
+{
+    /// Morphs through the waveforms in the order of [`MekkaWaveform::VARIANTS`], from `0` at the first one to `3` at the square.
+    /// Noise has no wavetable to morph to, so it's left out.
+    pub fn morph<F>(position: F) -> Morph<F, Self, Self>
+    where
+        F: Float
+    {
+        let last = u8::from(Self::Square) as usize;
+        let position = position.max(F::zero()).min(F::from(last).unwrap());
+        let i = position.floor().to_usize().unwrap_or(0).min(last - 1);
+        Morph {
+            a: Self::VARIANTS[i],
+            b: Self::VARIANTS[i + 1],
+            morph: position - F::from(i).unwrap()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::{error::Error, f64::consts::FRAC_1_SQRT_2};
+
+    use crate::{
+        waveform::{MekkaWaveform, Sawtooth, Sine, Waveform},
+        Wavetable
+    };
+
+    use super::Morph;
+
+    #[test]
+    fn it_works() -> Result<(), Box<dyn Error>>
+    {
+        crate::tests::print_waveform(MekkaWaveform::morph(1.5f32))
+    }
+
+    #[test]
+    fn magnitude_and_phase()
+    {
+        let cos = Wavetable::from_array(0.0, [(1.0, 0.0)]);
+        let sin = Wavetable::from_array(1.0, [(0.0, 1.0)]);
+        let (a, b) = cos.morph(&sin, 0.5).view().ab[0];
+        assert!((a - FRAC_1_SQRT_2).abs() < 1e-9 && (b - FRAC_1_SQRT_2).abs() < 1e-9);
+        assert_eq!(*cos.morph(&sin, 0.5).view().a0, 0.5);
+
+        let morph = Morph {
+            a: Sine,
+            b: Sawtooth,
+            morph: 0.25f64
+        };
+        let (sine, saw, morphed) = (
+            Waveform::<f64>::wavetable::<8>(&Sine).unwrap(),
+            Waveform::<f64>::wavetable::<8>(&Sawtooth).unwrap(),
+            morph.wavetable::<8>().unwrap()
+        );
+        for ((a, b), c) in sine.view().ab.iter().zip(saw.view().ab).zip(morphed.view().ab)
+        {
+            let r = a.0.hypot(a.1) + 0.25*(b.0.hypot(b.1) - a.0.hypot(a.1));
+            assert!((c.0.hypot(c.1) - r).abs() < 1e-9);
+        }
+
+        let morph = MekkaWaveform::morph(2.25f64);
+        assert_eq!((morph.a, morph.b, morph.morph), (MekkaWaveform::Sawtooth, MekkaWaveform::Square, 0.25));
+        assert!((morph.waveform(1.0) - (0.75*Sawtooth.waveform(1.0) + 0.25*MekkaWaveform::Square.waveform(1.0))).abs() < 1e-12);
+
+        let morph = MekkaWaveform::morph(3.5f64);
+        assert_eq!((morph.a, morph.b, morph.morph), (MekkaWaveform::Sawtooth, MekkaWaveform::Square, 1.0));
+        assert!(morph.wavetable::<8>().is_some());
+    }
+}
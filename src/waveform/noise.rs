@@ -1,7 +1,7 @@
 use num_traits::Float;
 use rand::distr::{uniform::SampleUniform, Distribution, Uniform};
 
-use super::Waveform;
+use super::{Waveform, WaveformExt};
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
 pub struct Noise;
@@ -21,6 +21,9 @@ where
         y.abs().powf(duty_cycle + duty_cycle).copysign(y)
     }
 }
+impl WaveformExt for Noise
+{
+}
 
 #[cfg(test)]
 mod test
@@ -0,0 +1,250 @@
+use num_traits::{Float, FloatConst};
+
+use crate::Wavetable;
+
+use super::{Waveform, WaveformExt};
+
+/// How many harmonics of the wavetable are used to measure the RMS of a [`Normalized`] waveform.
+pub const NORMALIZED_HARMONICS: usize = 256;
+/// How many points a period is sampled at, to measure the RMS of a [`Normalized`] waveform without a wavetable.
+pub const NORMALIZED_SAMPLES: usize = 1024;
+
+/// A waveform scaled to the loudness of a full-scale [`Sine`](super::Sine), by matching its RMS.
+///
+/// The RMS is measured once, from [`NORMALIZED_HARMONICS`] harmonics of the wavetable, or by sampling one period if there's none, and
+/// the same gain is used for the naive waveform and for wavetables of any size. So the level of a [`Wave`](crate::oscillator::Wave)
+/// doesn't jump where it switches between the two. It's measured at the duty cycle given with [`with_dtc`](Self::with_dtc) or
+/// [`set_duty_cycle`](Self::set_duty_cycle), and used as is at any other duty cycle the waveform is played at.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(
+    bound(serialize = "F: serde::Serialize, W: serde::Serialize", deserialize = "F: Float + FloatConst + serde::Deserialize<'de>, \
+        W: Waveform<F> + serde::Deserialize<'de>"),
+    from = "NormalizedSerde<F, W>"
+)]
+pub struct Normalized<F, W>
+{
+    waveform: W,
+    duty_cycle: Option<F>,
+    #[serde(skip)]
+    gain: F
+}
+
+/// The waveform and duty cycle as they're stored, before [`Normalized`] measures the gain.
+#[derive(serde::Deserialize)]
+struct NormalizedSerde<F, W>
+{
+    waveform: W,
+    duty_cycle: Option<F>
+}
+
+impl<F, W> From<NormalizedSerde<F, W>> for Normalized<F, W>
+where
+    F: Float + FloatConst,
+    W: Waveform<F>
+{
+    fn from(value: NormalizedSerde<F, W>) -> Self
+    {
+        Self::measure(value.waveform, value.duty_cycle)
+    }
+}
+
+impl<F, W> Normalized<F, W>
+where
+    F: Float + FloatConst,
+    W: Waveform<F>
+{
+    pub fn new(waveform: W) -> Self
+    {
+        Self::measure(waveform, None)
+    }
+    pub fn with_dtc(waveform: W, duty_cycle: F) -> Self
+    {
+        Self::measure(waveform, Some(duty_cycle))
+    }
+
+    pub fn inner(&self) -> &W
+    {
+        &self.waveform
+    }
+    /// Replaces the waveform, and measures it again.
+    pub fn set_inner(&mut self, waveform: W)
+    {
+        *self = Self::measure(waveform, self.duty_cycle)
+    }
+
+    pub fn into_inner(self) -> W
+    {
+        self.waveform
+    }
+
+    pub fn duty_cycle(&self) -> Option<F>
+    {
+        self.duty_cycle
+    }
+    /// Changes the duty cycle the waveform is measured at, and measures it again if it changed.
+    pub fn set_duty_cycle(&mut self, duty_cycle: Option<F>)
+    {
+        if self.duty_cycle != duty_cycle
+        {
+            self.duty_cycle = duty_cycle;
+            self.gain = Self::gain_of(&self.waveform, duty_cycle)
+        }
+    }
+
+    pub fn gain(&self) -> F
+    {
+        self.gain
+    }
+
+    fn measure(waveform: W, duty_cycle: Option<F>) -> Self
+    {
+        Self {
+            gain: Self::gain_of(&waveform, duty_cycle),
+            waveform,
+            duty_cycle
+        }
+    }
+
+    fn gain_of(waveform: &W, duty_cycle: Option<F>) -> F
+    {
+        let zero = F::zero();
+        let wavetable: Option<Wavetable<F, NORMALIZED_HARMONICS>> = match duty_cycle
+        {
+            Some(duty_cycle) => waveform.wavetable_with_dtc(duty_cycle),
+            None => waveform.wavetable()
+        };
+        let rms = wavetable.map(|wavetable| wavetable.rms())
+            .unwrap_or_else(|| {
+                let len = F::from(NORMALIZED_SAMPLES).unwrap();
+                let dtheta = F::TAU()/len;
+                let square = |k| {
+                    let theta = dtheta*(F::from(k).unwrap() + F::from(0.5).unwrap());
+                    let y = match duty_cycle
+                    {
+                        Some(duty_cycle) => waveform.waveform_with_dtc(theta, duty_cycle),
+                        None => waveform.waveform(theta)
+                    };
+                    y*y
+                };
+                ((0..NORMALIZED_SAMPLES).map(square).fold(zero, F::add)/len).sqrt()
+            });
+
+        if rms > zero
+        {
+            F::FRAC_1_SQRT_2()/rms
+        }
+        else
+        {
+            F::one()
+        }
+    }
+}
+
+impl<F, W> Default for Normalized<F, W>
+where
+    F: Float + FloatConst,
+    W: Waveform<F> + Default
+{
+    fn default() -> Self
+    {
+        Self::new(W::default())
+    }
+}
+
+impl<F, W> From<W> for Normalized<F, W>
+where
+    F: Float + FloatConst,
+    W: Waveform<F>
+{
+    fn from(waveform: W) -> Self
+    {
+        Self::new(waveform)
+    }
+}
+
+impl<F, W> Waveform<F> for Normalized<F, W>
+where
+    F: Float + FloatConst,
+    W: Waveform<F>
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        self.gain*self.waveform.waveform(theta)
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        self.gain*self.waveform.waveform_with_dtc(theta, duty_cycle)
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        self.waveform.wavetable().map(|w| w*self.gain)
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        self.waveform.wavetable_with_dtc(duty_cycle).map(|w| w*self.gain)
+    }
+}
+impl<F, W> WaveformExt for Normalized<F, W>
+{
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f64::consts::FRAC_1_SQRT_2;
+
+    use crate::{
+        waveform::{FnWaveform, MekkaWaveform, Sawtooth, Sine, Square, Waveform},
+        Wavetable
+    };
+
+    use super::{Normalized, NORMALIZED_HARMONICS};
+
+    #[test]
+    fn equal_loudness()
+    {
+        fn rms(waveform: &impl Waveform<f64>, duty_cycle: f64) -> f64
+        {
+            let len = 4096;
+            ((0..len).map(|k| waveform.waveform_with_dtc((k as f64 + 0.5)*core::f64::consts::TAU/len as f64, duty_cycle).powi(2))
+                .sum::<f64>()/len as f64).sqrt()
+        }
+
+        assert!((rms(&Sine, 0.5) - rms(&Square, 0.5)).abs() > 0.2);
+        for waveform in MekkaWaveform::VARIANTS.into_iter().filter(|w| !matches!(w, MekkaWaveform::Noise))
+        {
+            for duty_cycle in [0.1, 0.5, 0.8]
+            {
+                let normalized = Normalized::with_dtc(waveform, duty_cycle);
+                assert!((rms(&normalized, duty_cycle) - FRAC_1_SQRT_2).abs() < 1e-2);
+                if let Some(wavetable) = normalized.wavetable_with_dtc::<NORMALIZED_HARMONICS>(duty_cycle)
+                {
+                    let wavetable: Wavetable<f64, NORMALIZED_HARMONICS> = wavetable;
+                    assert!((wavetable.rms() - FRAC_1_SQRT_2).abs() < 1e-9);
+
+                    // Smaller wavetables only lose the level of the harmonics they leave out.
+                    let wavetable: Wavetable<f64, 64> = normalized.wavetable_with_dtc(duty_cycle).unwrap();
+                    assert!(wavetable.rms() <= FRAC_1_SQRT_2 + 1e-9 && wavetable.rms() > FRAC_1_SQRT_2 - 1e-2);
+                }
+            }
+        }
+        assert!((Normalized::new(Sine).waveform(1.0) - Sine.waveform(1.0f64)).abs() < 1e-12);
+
+        // Measured with a wavetable, and without one.
+        let waveform = FnWaveform::new(|theta: f64| theta.sin().signum());
+        assert!((rms(&Normalized::new(waveform), 0.5) - FRAC_1_SQRT_2).abs() < 1e-2);
+        assert!((rms(&Normalized::new(Sawtooth), 0.5) - FRAC_1_SQRT_2).abs() < 1e-2);
+
+        let mut normalized = Normalized::new(Sawtooth);
+        normalized.set_duty_cycle(Some(0.3));
+        assert_eq!(normalized, Normalized::with_dtc(Sawtooth, 0.3f64));
+        let json = serde_json::to_string(&normalized).unwrap();
+        assert_eq!(serde_json::from_str::<Normalized<f64, Sawtooth>>(&json).unwrap(), normalized);
+
+        fn copy_sync<T: Copy + Sync>(_: T)
+        {
+        }
+        copy_sync(normalized);
+    }
+}
@@ -0,0 +1,247 @@
+use num_traits::{Euclid, Float, FloatConst};
+
+use crate::Wavetable;
+
+use super::{breakpoints, Frames, Waveform, WaveformExt};
+
+/// The fewest points a period is sampled at, to find the wavetable of a [`PhaseDistortion`]. Enough for the highest resonance not
+/// to alias.
+const SAMPLES_MIN: usize = 256;
+/// The most points a period is sampled at, to find the wavetable of a [`PhaseDistortion`].
+const SAMPLES_MAX: usize = 4096;
+/// The highest resonance of [`PhaseDistortion::Resonance`], relative to the fundamental.
+const RESONANCE_MAX: f64 = 64.0;
+
+/// Phase-distortion waveforms, like on the Casio CZ series.
+///
+/// A cosine is driven by a warped phase, bent by the duty cycle like the DCW envelope on the CZ. At the default duty cycle, the
+/// phase isn't warped, so all but the double sine and the resonance are plain cosines. The wavetables are found numerically,
+/// with an FFT, so the shapes are band-limited in [`Wave`](crate::oscillator::Wave).
+///
+/// The FFT takes `4·N` points, rounded up to a power of two, from `256` to `4096`. A [`WaveDTC`](crate::oscillator::WaveDTC)
+/// rebuilds its wavetable whenever the duty cycle changes, so it's no good for sweeping the duty cycle. For that, like with the DCW
+/// envelope, [`frames`](PhaseDistortion::frames) finds the wavetables over a grid of duty cycles up front, for a
+/// [`Scan`](crate::oscillator::Scan) to crossfade between.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+pub enum PhaseDistortion
+{
+    /// The phase reaches half a period at the duty cycle, and goes slower after, for a sawtooth-like ramp as the duty cycle goes to
+    /// zero.
+    #[default]
+    Saw,
+    /// Each half of the cosine is squeezed into the start of its half period, for a square as the duty cycle goes to zero.
+    Square,
+    /// The whole cosine is squeezed into the start of the period, for a pulse as the duty cycle goes to zero.
+    Pulse,
+    /// A [saw](PhaseDistortion::Saw) at twice the frequency, played forwards and then backwards.
+    DoubleSine,
+    /// A cosine at `1/(1 - duty_cycle)` times the frequency, reset every period and faded out by a falling sawtooth window, like a
+    /// resonant filter.
+    Resonance
+}
+
+impl PhaseDistortion
+{
+    fn phase_distortion<F>(self, theta: F, duty_cycle: F) -> F
+    where
+        F: Float + FloatConst + Euclid
+    {
+        let zero = F::zero();
+        let one = F::one();
+        let pi = F::PI();
+        let tau = F::TAU();
+
+        let theta = theta.rem_euclid(&tau);
+        let d = duty_cycle.clamp(zero, one);
+        let squeeze = |theta: F, end: F| (theta*pi/(tau*d.max(F::epsilon()))).min(end);
+        match self
+        {
+            Self::Saw => breakpoints::dtc_unwarp(theta, d).cos(),
+            Self::Square => if theta < pi
+            {
+                squeeze(theta, pi)
+            }
+            else
+            {
+                pi + squeeze(theta - pi, pi)
+            }.cos(),
+            Self::Pulse => squeeze(theta, tau).cos(),
+            Self::DoubleSine => if theta < pi
+            {
+                breakpoints::dtc_unwarp(theta + theta, d).cos()
+            }
+            else
+            {
+                breakpoints::dtc_unwarp(tau + tau - theta - theta, d).cos()
+            },
+            Self::Resonance => {
+                let max = F::from(RESONANCE_MAX).unwrap();
+                let resonance = (one - d).recip().min(max);
+                let window = one - theta/tau;
+                one - window*(one - (resonance*theta).cos())
+            }
+        }
+    }
+}
+
+impl<F> Waveform<F> for PhaseDistortion
+where
+    F: Float + FloatConst + Euclid
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        self.phase_distortion(theta, crate::duty_cycle_default())
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        self.phase_distortion(theta, duty_cycle)
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        Some(self.sampled_wavetable(None))
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        Some(self.sampled_wavetable(Some(duty_cycle)))
+    }
+}
+impl WaveformExt for PhaseDistortion
+{
+}
+
+impl PhaseDistortion
+{
+    /// `K` wavetables at duty cycles spread evenly from `0` to `1`, starting at `duty_cycle`.
+    ///
+    /// Position `d·(K - 1)` plays duty cycle `d`, crossfading between the harmonics of the nearest two, so it's exact on the grid and
+    /// close between it for moderate `K`.
+    pub fn frames<F, const K: usize, const N: usize>(self, duty_cycle: F) -> Frames<F, K, N>
+    where
+        F: Float + FloatConst + Euclid
+    {
+        let last = F::from(K.saturating_sub(1).max(1)).unwrap();
+        let frames = core::array::from_fn(|k| self.sampled_wavetable(Some(F::from(k).unwrap()/last)));
+        Frames::new(frames, duty_cycle*last)
+    }
+
+    /// The wavetable, with an FFT of `4·N` points, rounded up to a power of two, from [`SAMPLES_MIN`] to [`SAMPLES_MAX`].
+    fn sampled_wavetable<F, const N: usize>(&self, duty_cycle: Option<F>) -> Wavetable<F, N>
+    where
+        F: Float + FloatConst + Euclid
+    {
+        macro_rules! with_samples {
+            ($($m:literal),*) => {
+                match N.saturating_mul(4).max(SAMPLES_MIN)
+                {
+                    $(samples if samples <= $m => Wavetable::from_waveform::<_, $m>(self, duty_cycle),)*
+                    _ => Wavetable::from_waveform::<_, SAMPLES_MAX>(self, duty_cycle)
+                }
+            };
+        }
+        with_samples!(256, 512, 1024, 2048)
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::{error::Error, f64::consts::TAU};
+
+    use crate::{
+        oscillator::{Oscillator, OscillatorState, Scan, WaveDTC},
+        waveform::Waveform,
+        Wavetable
+    };
+
+    use super::PhaseDistortion;
+
+    const SHAPES: [PhaseDistortion; 5] = [
+        PhaseDistortion::Saw,
+        PhaseDistortion::Square,
+        PhaseDistortion::Pulse,
+        PhaseDistortion::DoubleSine,
+        PhaseDistortion::Resonance
+    ];
+
+    #[test]
+    fn it_works() -> Result<(), Box<dyn Error>>
+    {
+        for shape in SHAPES
+        {
+            crate::tests::print_waveform(shape)?
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn shapes()
+    {
+        for shape in [PhaseDistortion::Saw, PhaseDistortion::Square, PhaseDistortion::Pulse]
+        {
+            for k in 0..16
+            {
+                let theta = k as f64*0.4;
+                assert!((shape.waveform(theta) - theta.cos()).abs() < 1e-12);
+            }
+        }
+
+        // The further from the default duty cycle, the brighter.
+        let thd = |duty_cycle| {
+            let wavetable: Wavetable<f64, 16> = PhaseDistortion::Saw.wavetable_with_dtc(duty_cycle).unwrap();
+            wavetable.thd()
+        };
+        assert!(thd(0.5) < 1e-9);
+        assert!(thd(0.05) > thd(0.2) && thd(0.2) > thd(0.4));
+
+        for shape in SHAPES
+        {
+            for duty_cycle in [0.1, 0.3, 0.8]
+            {
+                let wavetable: Wavetable<f64, 256> = shape.wavetable_with_dtc(duty_cycle).unwrap();
+                let error = (0..64).map(|k| {
+                    let theta = 0.05 + k as f64*0.1;
+                    (wavetable.waveform(theta, 256).unwrap() - shape.waveform_with_dtc(theta, duty_cycle)).abs()
+                })
+                .sum::<f64>()/64.0;
+                assert!(error < 2e-2);
+            }
+        }
+
+        // Sweeping the duty cycle through precomputed frames.
+        let frames = PhaseDistortion::Saw.frames::<f64, 17, 32>(0.5);
+        for (k, frame) in frames.frames().iter().enumerate()
+        {
+            let wavetable: Wavetable<f64, 32> = PhaseDistortion::Saw.wavetable_with_dtc(k as f64/16.0).unwrap();
+            assert_eq!(*frame, wavetable);
+        }
+        let mut scan = Oscillator::new(TAU*440.0, 0.0, Scan::from(frames));
+        for k in 0..256
+        {
+            scan.set_position(16.0*(0.5 - 0.4*k as f64/256.0));
+            assert!(scan.next(48000.0).is_finite());
+        }
+        let mut wave = Oscillator::new(TAU*1000.0, 0.0, WaveDTC::<_, _, 32>::from(PhaseDistortion::Saw));
+        scan.omega = TAU*1000.0;
+        scan.set_position(4.0);
+        scan.set_theta(0.0);
+        wave.set_duty_cycle(0.25);
+        for _ in 0..64
+        {
+            assert!((scan.next(48000.0) - wave.next(48000.0)).abs() < 1e-9);
+        }
+
+        // Fewer harmonics are found with a smaller FFT, to about the same values.
+        for shape in SHAPES
+        {
+            let small: Wavetable<f64, 16> = shape.wavetable_with_dtc(0.1).unwrap();
+            let large: Wavetable<f64, 1024> = shape.wavetable_with_dtc(0.1).unwrap();
+            assert!((small.a0() - large.a0()).abs() < 1e-2);
+            for n in 1..=16
+            {
+                let ((a0, b0), (a1, b1)) = (small.harmonic(n).unwrap(), large.harmonic(n).unwrap());
+                assert!((a0 - a1).abs() < 1e-2 && (b0 - b1).abs() < 1e-2);
+            }
+        }
+    }
+}
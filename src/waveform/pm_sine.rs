@@ -0,0 +1,204 @@
+use num_traits::Float;
+
+use crate::Wavetable;
+
+use super::{Waveform, WaveformExt};
+
+/// A sine phase-modulated by another sine, `cos(c·θ + I·sin(m·θ))`, with carrier `c` and modulator `m` as integer multiples of
+/// the fundamental, and modulation index `I`.
+///
+/// A rational ratio, like `3:2`, is given as `carrier: 3, modulator: 2`, with the oscillator at their common fundamental. Its
+/// wavetable is exact, from the Bessel function sidebands `J_k(I)` at `c + k·m`. With a duty cycle, the index is scaled by
+/// `2·duty_cycle`, so that `0.5` gives `I` and `0` gives a pure carrier.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+pub struct PmSine<F>
+{
+    pub carrier: u32,
+    pub modulator: u32,
+    pub index: F
+}
+
+impl<F> PmSine<F>
+where
+    F: Float
+{
+    pub const fn new(carrier: u32, modulator: u32, index: F) -> Self
+    {
+        Self {
+            carrier,
+            modulator,
+            index
+        }
+    }
+
+    fn index_with_dtc(&self, duty_cycle: F) -> F
+    {
+        self.index*duty_cycle/crate::duty_cycle_default()
+    }
+
+    fn pm(&self, theta: F, index: F) -> F
+    {
+        let c = F::from(self.carrier).unwrap();
+        let m = F::from(self.modulator).unwrap();
+        (c*theta + index*(m*theta).sin()).cos()
+    }
+
+    fn harmonics<const N: usize>(&self, index: F) -> Wavetable<F, N>
+    {
+        let zero = F::zero();
+        let c = self.carrier as i64;
+        let (m, x) = match self.modulator
+        {
+            0 => (1, 0.0),
+            m => (m as i64, index.to_f64().unwrap())
+        };
+
+        let mut a0 = 0.0;
+        let mut a = [0.0; N];
+        let mut add = |k: i64, j: f64| {
+            let n = (c + k*m).unsigned_abs() as usize;
+            if n == 0
+            {
+                a0 += j
+            }
+            else if let Some(a) = a.get_mut(n - 1)
+            {
+                *a += j
+            }
+        };
+
+        // Sidebands beyond this fall above harmonic N on both sides, or are too small to matter, which also keeps the Bessel functions
+        // from underflowing.
+        let k_max = ((N as i64 + c)/m).min(bessel_order_max(x));
+        let mut j_k = [0.0; BESSEL_CHUNK];
+        for start in (0..=k_max).step_by(BESSEL_CHUNK)
+        {
+            let end = (start + BESSEL_CHUNK as i64 - 1).min(k_max);
+            if rgsl::bessel::Jn_array(start as u32, end as u32, x, &mut j_k).is_err()
+            {
+                // Only underflow is expected, with orders so far past the index that the sidebands are negligible.
+                j_k = [0.0; BESSEL_CHUNK];
+            }
+            for (k, &j) in (start..=end).zip(j_k.iter())
+            {
+                add(k, j);
+                if k != 0
+                {
+                    add(-k, if k % 2 == 0 {j} else {-j});
+                }
+            }
+        }
+
+        Wavetable::from_array(F::from(a0).unwrap(), a.map(|a| (F::from(a).unwrap(), zero)))
+    }
+}
+
+impl<F> Waveform<F> for PmSine<F>
+where
+    F: Float
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        self.pm(theta, self.index)
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        self.pm(theta, self.index_with_dtc(duty_cycle))
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        Some(self.harmonics(self.index))
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        Some(self.harmonics(self.index_with_dtc(duty_cycle)))
+    }
+}
+impl<F> WaveformExt for PmSine<F>
+{
+}
+
+/// How many Bessel functions are computed at a time.
+const BESSEL_CHUNK: usize = 64;
+/// Margin, in orders and in multiples of `|x|^(1/3)`, past `|x|` where `J_k(x)` is below double precision.
+const BESSEL_MARGIN: f64 = 16.0;
+const BESSEL_MARGIN_CBRT: f64 = 10.0;
+
+/// The highest order of `J_k(x)` that isn't negligible. It falls off faster than exponentially once `k` passes `|x|`, over a
+/// transition about `|x|^(1/3)` wide.
+fn bessel_order_max(x: f64) -> i64
+{
+    let x = x.abs();
+    (x + BESSEL_MARGIN_CBRT*x.cbrt() + BESSEL_MARGIN).ceil() as i64
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::{error::Error, f64::consts::TAU};
+
+    use crate::{
+        oscillator::{Oscillator, Wave},
+        waveform::Waveform,
+        Wavetable
+    };
+
+    use super::PmSine;
+
+    #[test]
+    fn it_works() -> Result<(), Box<dyn Error>>
+    {
+        crate::tests::print_waveform(PmSine::new(1, 2, 1.5f32))
+    }
+
+    #[test]
+    fn bessel_sidebands()
+    {
+        for pm in [PmSine::new(1, 1, 2.0), PmSine::new(3, 2, 1.5), PmSine::new(1, 3, 4.0), PmSine::new(2, 0, 1.0)]
+        {
+            for duty_cycle in [None, Some(0.25)]
+            {
+                let wavetable: Wavetable<f64, 64> = match duty_cycle
+                {
+                    Some(duty_cycle) => pm.wavetable_with_dtc(duty_cycle),
+                    None => pm.wavetable()
+                }.unwrap();
+                for k in 0..32
+                {
+                    let theta = 0.1 + k as f64*0.2;
+                    let y = match duty_cycle
+                    {
+                        Some(duty_cycle) => pm.waveform_with_dtc(theta, duty_cycle),
+                        None => pm.waveform(theta)
+                    };
+                    assert!((wavetable.waveform(theta, 64).unwrap() - y).abs() < 1e-9);
+                }
+            }
+        }
+
+        // Far fewer sidebands than harmonics.
+        let pm = PmSine::new(1, 1, 0.01);
+        let wavetable: Wavetable<f64, 1024> = pm.wavetable().unwrap();
+        for k in 0..32
+        {
+            let theta = 0.1 + k as f64*0.2;
+            assert!((wavetable.waveform(theta, 1024).unwrap() - pm.waveform(theta)).abs() < 1e-9);
+        }
+        let mut osc = Oscillator::new(TAU*200.0, 0.0, Wave::<_, _, 256>::from(pm));
+        for _ in 0..64
+        {
+            let y = osc.next(48000.0);
+            assert!((y - pm.waveform(osc.theta())).abs() < 1e-9);
+        }
+
+        const RATE: f64 = 48000.0;
+        let pm = PmSine::new(3, 2, 1.5);
+        let mut osc = Oscillator::new(TAU*200.0, 0.0, Wave::<_, _, 256>::from(pm));
+        for _ in 0..64
+        {
+            let y = osc.next(RATE);
+            assert!((y - pm.waveform(osc.theta())).abs() < 1e-9);
+        }
+    }
+}
@@ -2,7 +2,7 @@ use num_traits::{Euclid, Float, FloatConst};
 
 use crate::Wavetable;
 
-use super::{Triangle, Waveform};
+use super::{Triangle, Waveform, WaveformExt};
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
 pub struct RoundedTriangle;
@@ -22,7 +22,8 @@ where
     }
     fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
     {
-        None
+        let zero = F::zero();
+        Some(Wavetable::from_fn(zero, |m| if m == 0 {(-F::one(), zero)} else {(zero, zero)}))
     }
     fn wavetable_with_dtc<const N: usize>(&self, mut duty_cycle: F) -> Option<Wavetable<F, N>>
     {
@@ -34,7 +35,7 @@ where
 
         if duty_cycle + duty_cycle == one
         {
-            return None
+            return self.wavetable()
         }
         duty_cycle = duty_cycle.clamp(zero, one);
         let d = tau*duty_cycle;
@@ -58,6 +59,9 @@ where
         }))
     }
 }
+impl WaveformExt for RoundedTriangle
+{
+}
 
 #[cfg(test)]
 mod test
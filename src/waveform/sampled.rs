@@ -0,0 +1,99 @@
+use num_traits::{Float, FloatConst};
+
+use crate::Wavetable;
+
+use super::{Waveform, WaveformExt};
+
+/// Makes any waveform band-limitable, by sampling it `M` times per period and finding its wavetable with an FFT when it doesn't
+/// have one of its own. See [`Wavetable::from_waveform`].
+///
+/// The wavetable is computed every time it's asked for, so this is best used with [`Wave`](crate::oscillator::Wave), which caches
+/// it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Sampled<W, const M: usize = 4096>(pub W);
+
+impl<F, W, const M: usize> Waveform<F> for Sampled<W, M>
+where
+    F: Float + FloatConst,
+    W: Waveform<F>
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        self.0.waveform(theta)
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        self.0.waveform_with_dtc(theta, duty_cycle)
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        self.0.wavetable()
+            .or_else(|| Some(Wavetable::from_waveform::<_, M>(&self.0, None)))
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        self.0.wavetable_with_dtc(duty_cycle)
+            .or_else(|| Some(Wavetable::from_waveform::<_, M>(&self.0, Some(duty_cycle))))
+    }
+}
+impl<W, const M: usize> WaveformExt for Sampled<W, M>
+{
+}
+
+#[cfg(test)]
+mod test
+{
+    use crate::{
+        waveform::{Sawtooth, Square, Waveform},
+        Wavetable
+    };
+
+    use super::Sampled;
+
+    struct Custom;
+
+    impl Waveform<f64> for Custom
+    {
+        fn waveform(&self, theta: f64) -> f64
+        {
+            0.25 + 0.5*(2.0*theta).sin() - (3.0*theta).cos()
+        }
+    }
+
+    #[test]
+    fn it_works()
+    {
+        let wavetable: Wavetable<f64, 8> = Sampled::<_, 64>(Custom).wavetable().unwrap();
+        let view = wavetable.view();
+        assert!((view.a0 - 0.25).abs() < 1e-9);
+        for (n, &(a, b)) in view.ab.iter().enumerate()
+        {
+            let (a_n, b_n) = match n + 1
+            {
+                2 => (0.0, 0.5),
+                3 => (-1.0, 0.0),
+                _ => (0.0, 0.0)
+            };
+            assert!((a - a_n).abs() < 1e-9 && (b - b_n).abs() < 1e-9);
+        }
+
+        for duty_cycle in [None, Some(0.3)]
+        {
+            let exact: Wavetable<f64, 16> = match duty_cycle
+            {
+                Some(duty_cycle) => Square.wavetable_with_dtc(duty_cycle),
+                None => Sawtooth.wavetable()
+            }.unwrap();
+            let sampled: Wavetable<f64, 16> = match duty_cycle
+            {
+                Some(duty_cycle) => Wavetable::from_waveform::<_, 4096>(&Square, Some(duty_cycle)),
+                None => Wavetable::from_waveform::<_, 4096>(&Sawtooth, None)
+            };
+            for (exact, sampled) in exact.view().ab.iter().zip(sampled.view().ab)
+            {
+                assert!((exact.0 - sampled.0).abs() < 1e-2 && (exact.1 - sampled.1).abs() < 1e-2);
+            }
+        }
+    }
+}
@@ -1,8 +1,10 @@
+use core::simd::{Simd, SimdElement};
+
 use num_traits::{Euclid, Float, FloatConst};
 
-use crate::Wavetable;
+use crate::{simd::FloatLanes, Wavetable};
 
-use super::Waveform;
+use super::{SimdWaveform, Waveform, WaveformExt};
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
 pub struct Sawtooth;
@@ -87,6 +89,25 @@ where
         }))
     }
 }
+impl WaveformExt for Sawtooth
+{
+}
+
+impl<F> SimdWaveform<F> for Sawtooth
+where
+    F: Float + FloatConst + Euclid + SimdElement
+{
+    fn waveform_simd<const L: usize>(&self, mut theta: Simd<F, L>) -> Simd<F, L>
+    where
+        Simd<F, L>: FloatLanes<F, L>
+    {
+        let pi = Simd::splat(F::PI());
+        let pi_half = Simd::splat(F::FRAC_PI_2());
+
+        theta = theta.rem_euclid(pi);
+        (theta - pi_half)/pi_half
+    }
+}
 
 #[cfg(test)]
 mod test
@@ -2,7 +2,7 @@ use num_traits::{Euclid, Float, FloatConst};
 
 use crate::Wavetable;
 
-use super::Waveform;
+use super::{Waveform, WaveformExt};
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
 pub struct Sine;
@@ -41,7 +41,8 @@ where
     }
     fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
     {
-        None
+        let zero = F::zero();
+        Some(Wavetable::from_fn(zero, |m| if m == 0 {(F::one(), zero)} else {(zero, zero)}))
     }
     fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
     {
@@ -49,7 +50,7 @@ where
 
         if duty_cycle == half
         {
-            return None
+            return self.wavetable()
         }
         let zero = F::zero();
         let one = F::one();
@@ -91,6 +92,9 @@ where
         ))
     }
 }
+impl WaveformExt for Sine
+{
+}
 
 const MAX_N: usize = 64;
 const MAX_I_N: f64 = f32::MAX_EXP as f64;
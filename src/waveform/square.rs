@@ -1,8 +1,10 @@
+use core::simd::{Simd, SimdElement};
+
 use num_traits::{Euclid, Float, FloatConst};
 
-use crate::Wavetable;
+use crate::{simd::FloatLanes, Wavetable};
 
-use super::Waveform;
+use super::{SimdWaveform, Waveform, WaveformExt};
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
 pub struct Square;
@@ -76,6 +78,26 @@ where
         }))
     }
 }
+impl WaveformExt for Square
+{
+}
+
+impl<F> SimdWaveform<F> for Square
+where
+    F: Float + FloatConst + Euclid + SimdElement
+{
+    fn waveform_simd<const L: usize>(&self, mut theta: Simd<F, L>) -> Simd<F, L>
+    where
+        Simd<F, L>: FloatLanes<F, L>
+    {
+        let one = Simd::splat(F::one());
+        let pi = Simd::splat(F::PI());
+        let tau = Simd::splat(F::TAU());
+
+        theta = theta.rem_euclid(tau);
+        theta.select_lt(pi, -one, one)
+    }
+}
 
 #[cfg(test)]
 mod test
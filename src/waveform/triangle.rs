@@ -1,8 +1,10 @@
+use core::simd::{Simd, SimdElement};
+
 use num_traits::{Euclid, Float, FloatConst};
 
-use crate::Wavetable;
+use crate::{simd::FloatLanes, Wavetable};
 
-use super::Waveform;
+use super::{SimdWaveform, Waveform, WaveformExt};
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
 pub struct Triangle;
@@ -100,6 +102,26 @@ where
         }))
     }
 }
+impl WaveformExt for Triangle
+{
+}
+
+impl<F> SimdWaveform<F> for Triangle
+where
+    F: Float + FloatConst + Euclid + SimdElement
+{
+    fn waveform_simd<const L: usize>(&self, mut theta: Simd<F, L>) -> Simd<F, L>
+    where
+        Simd<F, L>: FloatLanes<F, L>
+    {
+        let pi = Simd::splat(F::PI());
+        let tau = Simd::splat(F::TAU());
+
+        theta = theta.rem_euclid(tau);
+        let two_theta = theta + theta;
+        (pi - (two_theta - tau).abs())/pi
+    }
+}
 
 #[cfg(test)]
 mod test
@@ -0,0 +1,157 @@
+use num_traits::{Euclid, Float, FloatConst};
+
+use crate::Wavetable;
+
+use super::{Waveform, WaveformExt};
+
+/// How a [`Vector`] blends its waveforms.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+pub enum VectorBlend
+{
+    /// Bilinear crossfade of the outputs, or equally, of the harmonics.
+    #[default]
+    Linear,
+    /// Bilinear [`Wavetable::morph`], first along `x`, then along `y`. Only in a [`Wave`](crate::oscillator::Wave) or a
+    /// [`VectorWave`](crate::oscillator::VectorWave). Evaluated directly, the outputs are crossfaded, like with `Linear`.
+    Spectral
+}
+
+/// Vector synthesis, in the style of the Prophet VS. Blends four waveforms by a position on a square, like an XY pad.
+///
+/// The waveforms are at the corners `(0, 0)`, `(1, 0)`, `(0, 1)` and `(1, 1)`, in that order. Use an enum like
+/// [`MekkaWaveform`](super::MekkaWaveform) to blend different kinds of waveforms.
+///
+/// Moving the position of a `Vector` in a [`Wave`](crate::oscillator::Wave) rebuilds its wavetable. Use a
+/// [`VectorWave`](crate::oscillator::VectorWave) to modulate it every sample.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+pub struct Vector<F, W>
+{
+    pub corners: [W; 4],
+    pub x: F,
+    pub y: F,
+    pub blend: VectorBlend
+}
+
+impl<F, W> Vector<F, W>
+where
+    F: Float
+{
+    pub fn new(corners: [W; 4], blend: VectorBlend) -> Self
+    {
+        let zero = F::zero();
+        Self {
+            corners,
+            x: zero,
+            y: zero,
+            blend
+        }
+    }
+
+    /// Bilinear weights of the corners at the position.
+    pub fn weights(&self) -> [F; 4]
+    {
+        let zero = F::zero();
+        let one = F::one();
+        let x = self.x.max(zero).min(one);
+        let y = self.y.max(zero).min(one);
+        [(one - x)*(one - y), x*(one - y), (one - x)*y, x*y]
+    }
+
+    /// Blends the wavetables of the corners. Corners without a weight may be missing.
+    pub fn blend_wavetables<const N: usize>(&self, wavetables: [Option<&Wavetable<F, N>>; 4]) -> Option<Wavetable<F, N>>
+    where
+        F: FloatConst + Euclid
+    {
+        let zero = F::zero();
+        let one = F::one();
+        let weights = self.weights();
+
+        match self.blend
+        {
+            VectorBlend::Linear => {
+                let mut a0 = zero;
+                let mut ab = [(zero, zero); N];
+                for (w, wavetable) in weights.into_iter().zip(wavetables)
+                {
+                    if w == zero
+                    {
+                        continue
+                    }
+                    let wavetable = wavetable?.view();
+                    a0 = a0 + w**wavetable.a0;
+                    for ((a, b), &(a_n, b_n)) in ab.iter_mut().zip(wavetable.ab)
+                    {
+                        *a = *a + w*a_n;
+                        *b = *b + w*b_n;
+                    }
+                }
+                Some(Wavetable::from_array(a0, ab))
+            },
+            VectorBlend::Spectral => {
+                let x = self.x.max(zero).min(one);
+                let y = self.y.max(zero).min(one);
+                let morph = |w0: Option<&Wavetable<F, N>>, w1: Option<&Wavetable<F, N>>, t: F| if t == zero
+                {
+                    w0.copied()
+                }
+                else if t == one
+                {
+                    w1.copied()
+                }
+                else
+                {
+                    Some(w0?.morph(w1?, t))
+                };
+                let [w00, w10, w01, w11] = wavetables;
+                morph(morph(w00, w10, x).as_ref(), morph(w01, w11, x).as_ref(), y)
+            }
+        }
+    }
+
+    fn wavetables<const N: usize>(&self, wavetable: impl Fn(&W) -> Option<Wavetable<F, N>>) -> Option<Wavetable<F, N>>
+    where
+        F: FloatConst + Euclid
+    {
+        let zero = F::zero();
+        let weights = self.weights();
+        let wavetables: [_; 4] = core::array::from_fn(|i| if weights[i] != zero {wavetable(&self.corners[i])} else {None});
+        self.blend_wavetables(wavetables.each_ref().map(Option::as_ref))
+    }
+
+    fn crossfade(&self, waveform: impl Fn(&W) -> F) -> F
+    {
+        let zero = F::zero();
+        self.weights()
+            .into_iter()
+            .zip(&self.corners)
+            .filter(|&(w, _)| w != zero)
+            .fold(zero, |y, (w, corner)| y + w*waveform(corner))
+    }
+}
+
+impl<F, W> Waveform<F> for Vector<F, W>
+where
+    F: Float + FloatConst + Euclid,
+    W: Waveform<F>
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        self.crossfade(|w| w.waveform(theta))
+    }
+    fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
+    {
+        self.crossfade(|w| w.waveform_with_dtc(theta, duty_cycle))
+    }
+
+    fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+    {
+        self.wavetables(W::wavetable)
+    }
+    fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+    {
+        self.wavetables(|w| w.wavetable_with_dtc(duty_cycle))
+    }
+}
+impl<F, W> WaveformExt for Vector<F, W>
+{
+}
@@ -1,11 +1,13 @@
-use num_traits::Float;
-
-use crate as oscillation;
-
-use crate::Wavetable;
-
 use super::{Noise, Sawtooth, Sine, Square, Triangle};
 
+/// Declares an enum that is a [`Waveform`](crate::waveform::Waveform), choosing between the waveforms it lists.
+///
+/// Enums listed with an integer representation have unit variants named after each waveform, which then must be unit structs.
+/// Otherwise, each variant holds its waveform, so any waveform type with a name can be used, like a type alias for a
+/// [`FnWaveform`](crate::waveform::FnWaveform).
+///
+/// Only the unit variant form derives the standard traits. Any other derives, like serde's, go with the attributes on the enum.
+#[macro_export]
 macro_rules! waveforms {
     (
         $(#[$meta:meta])*
@@ -16,7 +18,7 @@ macro_rules! waveforms {
         $($($more:tt)+)?
     ) => {
         $(#[$meta])*
-        #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
         $v enum $group
         {
             $($w),+
@@ -42,39 +44,43 @@ macro_rules! waveforms {
             }
         )?
 
-        impl<F> oscillation::waveform::Waveform<F> for $group
+        impl $crate::waveform::WaveformExt for $group
+        {
+        }
+
+        impl<F> $crate::waveform::Waveform<F> for $group
         where
-            F: Float,
-            $($w: oscillation::waveform::Waveform<F>),+
+            F: $crate::num_traits::Float,
+            $($w: $crate::waveform::Waveform<F>),+
         {
             fn waveform(&self, theta: F) -> F
             {
                 match self
                 {
-                    $(Self::$w => $w.waveform(theta)),+
+                    $(Self::$w => $crate::waveform::Waveform::waveform(&$w, theta)),+
                 }
             }
             fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
             {
                 match self
                 {
-                    $(Self::$w => $w.waveform_with_dtc(theta, duty_cycle)),+
+                    $(Self::$w => $crate::waveform::Waveform::waveform_with_dtc(&$w, theta, duty_cycle)),+
                 }
             }
 
-            fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+            fn wavetable<const N: usize>(&self) -> Option<$crate::Wavetable<F, N>>
             {
                 match self
                 {
-                    $(Self::$w => $w.wavetable()),+
+                    $(Self::$w => $crate::waveform::Waveform::wavetable(&$w)),+
                 }
             }
 
-            fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+            fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<$crate::Wavetable<F, N>>
             {
                 match self
                 {
-                    $(Self::$w => $w.wavetable_with_dtc(duty_cycle)),+
+                    $(Self::$w => $crate::waveform::Waveform::wavetable_with_dtc(&$w, duty_cycle)),+
                 }
             }
         }
@@ -105,7 +111,7 @@ macro_rules! waveforms {
         }
 
         $(
-            oscillation::waveforms!($($more)*);
+            $crate::waveforms!($($more)*);
         )?
     };
     (
@@ -117,7 +123,6 @@ macro_rules! waveforms {
         $($($more:tt)+)?
     ) => {
         $(#[$meta])*
-        #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
         $v enum $group
         {
             $($w($w)),+
@@ -143,39 +148,43 @@ macro_rules! waveforms {
             }
         )?
 
-        impl<F> oscillation::waveform::Waveform<F> for $group
+        impl $crate::waveform::WaveformExt for $group
+        {
+        }
+
+        impl<F> $crate::waveform::Waveform<F> for $group
         where
-            F: Float,
-            $($w: oscillation::waveform::Waveform<F>),+
+            F: $crate::num_traits::Float,
+            $($w: $crate::waveform::Waveform<F>),+
         {
             fn waveform(&self, theta: F) -> F
             {
                 match self
                 {
-                    $(Self::$w(w) => w.waveform(theta)),+
+                    $(Self::$w(w) => $crate::waveform::Waveform::waveform(w, theta)),+
                 }
             }
             fn waveform_with_dtc(&self, theta: F, duty_cycle: F) -> F
             {
                 match self
                 {
-                    $(Self::$w(w) => w.waveform_with_dtc(theta, duty_cycle)),+
+                    $(Self::$w(w) => $crate::waveform::Waveform::waveform_with_dtc(w, theta, duty_cycle)),+
                 }
             }
 
-            fn wavetable<const N: usize>(&self) -> Option<Wavetable<F, N>>
+            fn wavetable<const N: usize>(&self) -> Option<$crate::Wavetable<F, N>>
             {
                 match self
                 {
-                    $(Self::$w(w) => w.wavetable()),+
+                    $(Self::$w(w) => $crate::waveform::Waveform::wavetable(w)),+
                 }
             }
 
-            fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<Wavetable<F, N>>
+            fn wavetable_with_dtc<const N: usize>(&self, duty_cycle: F) -> Option<$crate::Wavetable<F, N>>
             {
                 match self
                 {
-                    $(Self::$w(w) => w.wavetable_with_dtc(duty_cycle)),+
+                    $(Self::$w(w) => $crate::waveform::Waveform::wavetable_with_dtc(w, duty_cycle)),+
                 }
             }
         }
@@ -187,12 +196,13 @@ macro_rules! waveforms {
         }
 
         $(
-            oscillation::waveforms!($($more)*);
+            $crate::waveforms!($($more)*);
         )?
     };
 }
 
 waveforms!(
+    #[derive(serde::Serialize, serde::Deserialize)]
     pub enum MekkaWaveform: u8 = Sine
     {
         Sine,
@@ -208,11 +218,33 @@ mod test
 {
     use core::error::Error;
 
+    use crate::waveform::{FnWaveform, Sine, Waveform};
+
     use super::MekkaWaveform;
 
+    type Bump = FnWaveform<fn(f32) -> f32>;
+
+    waveforms!(
+        #[derive(Clone, Copy)]
+        enum Custom
+        {
+            Bump,
+            Sine
+        }
+    );
+
     #[test]
     fn it_works() -> Result<(), Box<dyn Error>>
     {
         crate::tests::print_waveform(MekkaWaveform::Triangle)
     }
+
+    #[test]
+    fn fn_waveform()
+    {
+        let custom = Custom::from(Bump::new(|theta| theta.cos().max(0.0)));
+        assert_eq!(custom.waveform(0.0), 1.0);
+        assert!(custom.wavetable::<8>().is_none());
+        assert!(Custom::from(Sine).wavetable_with_dtc::<8>(0.25).is_some());
+    }
 }
\ No newline at end of file
@@ -1,7 +1,18 @@
+use core::{
+    fmt,
+    marker::PhantomData,
+    ops::{Add, Mul, Neg, Sub},
+    simd::{Simd, SimdElement}
+};
+
 use num_complex::Complex;
-use num_traits::Float;
+use num_traits::{Euclid, Float, FloatConst};
+use serde::{
+    de::{self, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer
+};
 
-use crate::util;
+use crate::{fft, simd::FloatLanes, util, waveform::{Waveform, WaveformExt}};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Wavetable<F, const N: usize>
@@ -13,6 +24,7 @@ where
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename = "Wavetable")]
 pub struct WavetableView<'a, F>
 where
     F: Float
@@ -38,6 +50,113 @@ where
         }
     }
 
+    /// Finds the first `N` harmonics of one period, sampled evenly over `[0, 2π)`, with a DFT.
+    ///
+    /// Harmonics at or above half the number of samples can't be told apart from the lower ones, and are left silent.
+    pub fn from_samples(samples: impl ExactSizeIterator<Item = F>) -> Self
+    where
+        F: FloatConst
+    {
+        let zero = F::zero();
+        let tau = F::TAU();
+
+        let len = samples.len();
+        let mut a0 = zero;
+        let mut ab = [(zero, zero); N];
+        if len == 0
+        {
+            return Self::from_array(a0, ab)
+        }
+
+        let up_to = N.min((len - 1)/2);
+        let dtheta = tau/F::from(len).unwrap();
+        for (k, x) in samples.enumerate()
+        {
+            a0 = a0 + x;
+
+            let exp_1 = Complex::cis(dtheta*F::from(k).unwrap());
+            let mut exp_n = exp_1;
+            for (a, b) in ab[..up_to].iter_mut()
+            {
+                *a = *a + x*exp_n.re;
+                *b = *b + x*exp_n.im;
+                util::mul_assign(&mut exp_n, exp_1);
+            }
+        }
+
+        let len = F::from(len).unwrap();
+        let g = (len/(F::one() + F::one())).recip();
+        Self::from_array(a0/len, ab.map(|(a, b)| (a*g, b*g)))
+    }
+
+    /// Samples one period of a waveform `M` times, and finds its first `N` harmonics with an FFT.
+    ///
+    /// `M` must be a power of two. It should be well above `2N`, since waveforms with discontinuities or sharp corners alias when
+    /// sampled, and the oversampling keeps that out of the harmonics that are kept.
+    pub fn from_waveform<W, const M: usize>(waveform: &W, duty_cycle: Option<F>) -> Self
+    where
+        F: FloatConst,
+        W: Waveform<F>
+    {
+        let zero = F::zero();
+        let len = F::from(M).unwrap();
+        let dtheta = F::TAU()/len;
+
+        let mut x: [Complex<F>; M] = core::array::from_fn(|k| {
+            let theta = dtheta*F::from(k).unwrap();
+            let x = match duty_cycle
+            {
+                Some(duty_cycle) => waveform.waveform_with_dtc(theta, duty_cycle),
+                None => waveform.waveform(theta)
+            };
+            Complex::new(x, zero)
+        });
+        fft::fft(&mut x);
+
+        let g = (len/(F::one() + F::one())).recip();
+        let up_to = N.min(M.saturating_sub(1)/2);
+        Self::from_fn(x[0].re/len, |m| if m < up_to
+        {
+            let x = x[m + 1];
+            (x.re*g, -x.im*g)
+        }
+        else
+        {
+            (zero, zero)
+        })
+    }
+
+    /// Morphs towards another wavetable, from `self` at `t = 0` to `other` at `t = 1`.
+    ///
+    /// Each harmonic's magnitude is interpolated linearly, and its phase along the shortest way around, so harmonics that are out
+    /// of phase don't cancel out halfway like they would in a crossfade.
+    pub fn morph(&self, other: &Self, t: F) -> Self
+    where
+        F: FloatConst + Euclid
+    {
+        let pi = F::PI();
+        let tau = F::TAU();
+        let lerp = |x0: F, x1: F| x0 + (x1 - x0)*t;
+
+        Self::from_fn(lerp(self.a0, other.a0), |n| {
+            let ((a0, b0), (a1, b1)) = (self.ab[n], other.ab[n]);
+            let (r0, r1) = (a0.hypot(b0), a1.hypot(b1));
+            let (mut p0, mut p1) = (b0.atan2(a0), b1.atan2(a1));
+            if r0 == F::zero()
+            {
+                p0 = p1
+            }
+            if r1 == F::zero()
+            {
+                p1 = p0
+            }
+            let dp = (p1 - p0 + pi).rem_euclid(&tau) - pi;
+            let (s, c) = (p0 + dp*t).sin_cos();
+            let r = lerp(r0, r1);
+            (r*c, r*s)
+        })
+    }
+
     pub fn waveform(&self, theta: F, up_to: usize) -> Option<F>
     {
         let exp_1 = Complex::cis(theta);
@@ -57,6 +176,56 @@ where
         Some(y)
     }
 
+    /// Evaluates the analytic signal `y + iŷ` at `theta`, where `ŷ` is the [Hilbert transform](Wavetable::hilbert), with the same
+    /// harmonics as [`Wavetable::waveform`].
+    pub fn analytic(&self, theta: F, up_to: usize) -> Option<Complex<F>>
+    {
+        let exp_1 = Complex::cis(theta);
+        let mut exp_n = exp_1;
+
+        let y = Complex::new(self.a0, F::zero()) + util::sum(self.ab[..up_to.min(N)].iter()
+            .copied()
+            .map(|(a, b)| {
+                let y = Complex::new(a*exp_n.re + b*exp_n.im, a*exp_n.im - b*exp_n.re);
+                util::mul_assign(&mut exp_n, exp_1);
+                y
+            }));
+        if !(y.re.is_finite() && y.im.is_finite())
+        {
+            return None
+        }
+        Some(y)
+    }
+
+    /// Evaluates the table in `L` lanes at once, summing up to `up_to[l]` harmonics in lane `l`.
+    ///
+    /// Non-finite lanes are returned as-is, so the caller can fall back on the waveform.
+    pub fn waveform_simd<const L: usize>(&self, theta: Simd<F, L>, up_to: [usize; L]) -> Simd<F, L>
+    where
+        F: SimdElement,
+        Simd<F, L>: FloatLanes<F, L>
+    {
+        let zero = Simd::splat(F::zero());
+
+        let exp_1 = theta.to_array().map(Float::sin_cos);
+        let s1 = Simd::from_array(exp_1.map(|(s, _)| s));
+        let c1 = Simd::from_array(exp_1.map(|(_, c)| c));
+        let up_to = up_to.map(|n| n.min(N));
+        let limit = Simd::from_array(up_to.map(|n| F::from(n).unwrap()));
+
+        let mut re = c1;
+        let mut im = s1;
+        let mut y = Simd::splat(self.a0);
+        for (m, &(a, b)) in self.ab[..up_to.into_iter().max().unwrap_or(0)].iter().enumerate()
+        {
+            let n = Simd::splat(F::from(m + 1).unwrap());
+            let y_n = Simd::splat(a)*re + Simd::splat(b)*im;
+            y += limit.select_lt(n, zero, y_n);
+            (re, im) = (re*c1 - im*s1, re*s1 + im*c1);
+        }
+        y
+    }
+
     pub fn truncate<const M: usize>(self) -> Option<Wavetable<F, M>>
     {
         self.ab.into_iter()
@@ -68,9 +237,539 @@ where
             })
     }
 
+    /// Like [`Wavetable::truncate`], but pads with silent harmonics instead of failing if `M > N`.
+    pub fn resize<const M: usize>(self) -> Wavetable<F, M>
+    {
+        let zero = F::zero();
+        Wavetable {
+            a0: self.a0,
+            ab: core::array::from_fn(|n| self.ab.get(n).copied().unwrap_or((zero, zero)))
+        }
+    }
+
     pub const fn view(&self) -> WavetableView<'_, F>
     {
         let Self { a0: dc, ab: table } = self;
         WavetableView { a0: dc, ab: table }
     }
-}
\ No newline at end of file
+
+    /// The DC-offset.
+    pub const fn a0(&self) -> F
+    {
+        self.a0
+    }
+    pub const fn a0_mut(&mut self) -> &mut F
+    {
+        &mut self.a0
+    }
+
+    /// The coefficients `(a, b)` of harmonic `n`, counting from `1`, in `a·cos(nθ) + b·sin(nθ)`.
+    pub fn harmonic(&self, n: usize) -> Option<(F, F)>
+    {
+        self.ab.get(n.checked_sub(1)?).copied()
+    }
+    pub fn harmonic_mut(&mut self, n: usize) -> Option<&mut (F, F)>
+    {
+        self.ab.get_mut(n.checked_sub(1)?)
+    }
+
+    /// The magnitude and phase `(r, φ)` of harmonic `n`, counting from `1`, in `r·cos(nθ - φ)`.
+    pub fn polar(&self, n: usize) -> Option<(F, F)>
+    {
+        let (a, b) = self.harmonic(n)?;
+        Some((a.hypot(b), b.atan2(a)))
+    }
+    /// Sets the magnitude and phase of harmonic `n`, counting from `1`. Does nothing if there's no such harmonic.
+    pub fn set_polar(&mut self, n: usize, magnitude: F, phase: F)
+    {
+        if let Some(ab) = self.harmonic_mut(n)
+        {
+            let (s, c) = phase.sin_cos();
+            *ab = (magnitude*c, magnitude*s)
+        }
+    }
+
+    pub fn map_harmonics(mut self, mut map: impl FnMut(usize, (F, F)) -> (F, F)) -> Self
+    {
+        for (m, ab) in self.ab.iter_mut().enumerate()
+        {
+            *ab = map(m + 1, *ab)
+        }
+        self
+    }
+
+    /// Silences the even harmonics, leaving the DC-offset as is.
+    pub fn remove_even(self) -> Self
+    {
+        let zero = F::zero();
+        self.map_harmonics(|n, ab| if n % 2 == 0 {(zero, zero)} else {ab})
+    }
+    /// Silences the odd harmonics.
+    pub fn remove_odd(self) -> Self
+    {
+        let zero = F::zero();
+        self.map_harmonics(|n, ab| if n % 2 == 1 {(zero, zero)} else {ab})
+    }
+
+    /// Tilts the spectrum by `db_per_octave` decibels per octave, relative to the fundamental.
+    pub fn tilt(self, db_per_octave: F) -> Self
+    {
+        let twenty = F::from(20.0).unwrap();
+        let two = F::one() + F::one();
+        let g = db_per_octave/(twenty*two.log10());
+        self.map_harmonics(|n, (a, b)| {
+            let g = F::from(n).unwrap().powf(g);
+            (a*g, b*g)
+        })
+    }
+
+    /// Adds `angle` to the phase of every harmonic, which changes the shape but not the magnitudes.
+    pub fn rotate_phases(self, angle: F) -> Self
+    {
+        let (s, c) = angle.sin_cos();
+        self.map_harmonics(|_, (a, b)| (a*c - b*s, a*s + b*c))
+    }
+
+    /// Shifts the waveform earlier by `theta` radians, so the table at phase `0` is what it was at `theta`.
+    pub fn phase_shift(self, theta: F) -> Self
+    {
+        self.map_harmonics(|n, (a, b)| {
+            let (s, c) = (F::from(n).unwrap()*theta).sin_cos();
+            (a*c + b*s, b*c - a*s)
+        })
+    }
+
+    /// The derivative with respect to the phase.
+    pub fn derivative(self) -> Self
+    {
+        self.zero_dc().map_harmonics(|n, (a, b)| {
+            let n = F::from(n).unwrap();
+            (n*b, -n*a)
+        })
+    }
+
+    /// The antiderivative with respect to the phase, with no DC-offset.
+    ///
+    /// The DC-offset would integrate to a ramp, which isn't periodic, so it's left out.
+    pub fn integral(self) -> Self
+    {
+        self.zero_dc().map_harmonics(|n, (a, b)| {
+            let n = F::from(n).unwrap();
+            (-b/n, a/n)
+        })
+    }
+
+    /// The Hilbert transform, which delays every harmonic by a quarter of its period, and removes the DC-offset.
+    pub fn hilbert(self) -> Self
+    {
+        self.zero_dc().map_harmonics(|_, (a, b)| (-b, a))
+    }
+
+    /// The wavetable of the waveform played `ratio` times as fast, with harmonic `n` moved to `ratio·n`. Harmonics moved past `N`
+    /// are left out.
+    pub fn at_ratio(&self, ratio: usize) -> Self
+    {
+        let zero = F::zero();
+        if ratio == 0
+        {
+            return Self::from_fn(Wavetable::waveform(self, zero, N).unwrap_or(zero), |_| (zero, zero))
+        }
+        Self::from_fn(self.a0, |m| if (m + 1) % ratio == 0 {self.ab[(m + 1)/ratio - 1]} else {(zero, zero)})
+    }
+
+    /// The wavetable of the product of two waveforms, by convolving their spectra. Harmonics past `N` are left out, and so are
+    /// the contributions of harmonics the tables don't have, so it's only exact for waveforms that fit in the tables.
+    pub fn product(&self, other: &Self) -> Self
+    {
+        let zero = F::zero();
+        let half = F::from(0.5).unwrap();
+
+        // The two-sided spectrum, with y = Σ c[k]·e^(ikθ).
+        let c = |wavetable: &Self, k: isize| match k
+        {
+            0 => Complex::new(wavetable.a0, zero),
+            k => {
+                let (a, b) = wavetable.ab[k.unsigned_abs() - 1];
+                Complex::new(a*half, if k > 0 {-b*half} else {b*half})
+            }
+        };
+        let n_max = N as isize;
+        let c_n = |n: isize| util::sum(((n - n_max).max(-n_max)..=n_max.min(n + n_max))
+            .map(|k| c(self, k)*c(other, n - k)));
+
+        let dc = c_n(0);
+        Self::from_fn(dc.re, |m| {
+            let c = c_n(m as isize + 1);
+            (c.re + c.re, -c.im - c.im)
+        })
+    }
+
+    pub fn zero_dc(mut self) -> Self
+    {
+        self.a0 = F::zero();
+        self
+    }
+
+    /// The peak absolute value of the waveform, found by sampling it over one period.
+    pub fn peak(&self) -> F
+    {
+        let len = PEAK_OVERSAMPLING*(2*N + 1);
+        let dtheta = F::from(core::f64::consts::TAU/len as f64).unwrap();
+        (0..len).map(|k| Wavetable::waveform(self, dtheta*F::from(k).unwrap(), N).unwrap_or_else(F::zero).abs())
+            .fold(F::zero(), F::max)
+    }
+
+    /// Scales the wavetable so its peak is at `±1`. Silent tables are left as is.
+    pub fn normalize(self) -> Self
+    {
+        let peak = self.peak();
+        if peak > F::zero()
+        {
+            self*peak.recip()
+        }
+        else
+        {
+            self
+        }
+    }
+
+    /// The mean square of the waveform over one period, from its harmonics by Parseval's theorem.
+    pub fn energy(&self) -> F
+    {
+        let half = F::from(0.5).unwrap();
+        self.a0*self.a0 + half*util::sum(self.ab.iter().map(|&(a, b)| a*a + b*b))
+    }
+
+    pub fn rms(&self) -> F
+    {
+        self.energy().sqrt()
+    }
+
+    /// The ratio of the [peak](Wavetable::peak) to the [RMS](Wavetable::rms). NaN for silent tables.
+    pub fn crest_factor(&self) -> F
+    {
+        self.peak()/self.rms()
+    }
+
+    /// The total harmonic distortion, as the RMS of the overtones relative to the fundamental. Infinite without a fundamental.
+    pub fn thd(&self) -> F
+    {
+        let Some(&(a1, b1)) = self.ab.first()
+        else
+        {
+            return F::zero()
+        };
+        util::sum(self.ab[1..].iter().map(|&(a, b)| a*a + b*b)).sqrt()/a1.hypot(b1)
+    }
+
+    /// Scales the wavetable so its RMS is that of a full-scale sine, `1/√2`. Silent tables are left as is.
+    pub fn normalize_rms(self) -> Self
+    {
+        let rms = self.rms();
+        if rms > F::zero()
+        {
+            self*(F::from(core::f64::consts::FRAC_1_SQRT_2).unwrap()/rms)
+        }
+        else
+        {
+            self
+        }
+    }
+}
+
+/// How many times the highest harmonic's Nyquist rate a wavetable is sampled at to find its peak.
+const PEAK_OVERSAMPLING: usize = 8;
+
+impl<F, const N: usize> Waveform<F> for Wavetable<F, N>
+where
+    F: Float
+{
+    fn waveform(&self, theta: F) -> F
+    {
+        Wavetable::waveform(self, theta, N).unwrap_or_else(F::zero)
+    }
+
+    fn wavetable<const M: usize>(&self) -> Option<Wavetable<F, M>>
+    {
+        Some(self.resize())
+    }
+}
+impl<F, const N: usize> WaveformExt for Wavetable<F, N>
+where
+    F: Float
+{
+}
+
+impl<F, const N: usize> Add for Wavetable<F, N>
+where
+    F: Float
+{
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output
+    {
+        Self::from_fn(self.a0 + rhs.a0, |n| (self.ab[n].0 + rhs.ab[n].0, self.ab[n].1 + rhs.ab[n].1))
+    }
+}
+impl<F, const N: usize> Sub for Wavetable<F, N>
+where
+    F: Float
+{
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output
+    {
+        Self::from_fn(self.a0 - rhs.a0, |n| (self.ab[n].0 - rhs.ab[n].0, self.ab[n].1 - rhs.ab[n].1))
+    }
+}
+impl<F, const N: usize> Neg for Wavetable<F, N>
+where
+    F: Float
+{
+    type Output = Self;
+
+    fn neg(self) -> Self::Output
+    {
+        Self::from_array(-self.a0, self.ab.map(|(a, b)| (-a, -b)))
+    }
+}
+impl<F, const N: usize> Mul<F> for Wavetable<F, N>
+where
+    F: Float
+{
+    type Output = Self;
+
+    fn mul(self, rhs: F) -> Self::Output
+    {
+        Self::from_array(self.a0*rhs, self.ab.map(|(a, b)| (a*rhs, b*rhs)))
+    }
+}
+
+impl<F, const N: usize> Serialize for Wavetable<F, N>
+where
+    F: Float + Serialize
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        self.view().serialize(serializer)
+    }
+}
+
+/// Tables of any length can be loaded. Missing harmonics are silent, and excess harmonics are dropped.
+impl<'de, F, const N: usize> Deserialize<'de> for Wavetable<F, N>
+where
+    F: Float + Deserialize<'de>
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>
+    {
+        deserializer.deserialize_struct("Wavetable", FIELDS, WavetableVisitor(PhantomData))
+    }
+}
+
+const FIELDS: &[&str] = &["a0", "ab"];
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum Field
+{
+    A0,
+    Ab,
+    #[serde(other)]
+    Other
+}
+
+struct WavetableVisitor<F, const N: usize>(PhantomData<F>);
+
+impl<'de, F, const N: usize> Visitor<'de> for WavetableVisitor<F, N>
+where
+    F: Float + Deserialize<'de>
+{
+    type Value = Wavetable<F, N>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result
+    {
+        formatter.write_str("a wavetable")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>
+    {
+        let a0 = seq.next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let Harmonics(ab) = seq.next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(Wavetable::from_array(a0, ab))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>
+    {
+        let mut a0 = None;
+        let mut ab = None;
+        while let Some(key) = map.next_key()?
+        {
+            match key
+            {
+                Field::A0 => a0 = Some(map.next_value()?),
+                Field::Ab => ab = Some(map.next_value::<Harmonics<F, N>>()?.0),
+                Field::Other => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(Wavetable::from_array(
+            a0.ok_or_else(|| de::Error::missing_field("a0"))?,
+            ab.ok_or_else(|| de::Error::missing_field("ab"))?
+        ))
+    }
+}
+
+struct Harmonics<F, const N: usize>([(F, F); N]);
+
+impl<'de, F, const N: usize> Deserialize<'de> for Harmonics<F, N>
+where
+    F: Float + Deserialize<'de>
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>
+    {
+        deserializer.deserialize_seq(HarmonicsVisitor(PhantomData))
+    }
+}
+
+struct HarmonicsVisitor<F, const N: usize>(PhantomData<F>);
+
+impl<'de, F, const N: usize> Visitor<'de> for HarmonicsVisitor<F, N>
+where
+    F: Float + Deserialize<'de>
+{
+    type Value = Harmonics<F, N>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result
+    {
+        formatter.write_str("a sequence of harmonics")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>
+    {
+        let zero = F::zero();
+        let mut ab = [(zero, zero); N];
+        for n in 0..N
+        {
+            match seq.next_element()?
+            {
+                Some(harmonic) => ab[n] = harmonic,
+                None => return Ok(Harmonics(ab))
+            }
+        }
+        while seq.next_element::<IgnoredAny>()?.is_some()
+        {
+
+        }
+        Ok(Harmonics(ab))
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::Wavetable;
+
+    #[test]
+    fn serde_any_length()
+    {
+        let wavetable = Wavetable::<f32, 4>::from_fn(0.5, |n| (n as f32, -(n as f32)));
+        let json = serde_json::to_string(&wavetable).unwrap();
+
+        let shorter: Wavetable<f32, 2> = serde_json::from_str(&json).unwrap();
+        assert_eq!(Some(shorter), wavetable.truncate());
+
+        let longer: Wavetable<f32, 8> = serde_json::from_str(&json).unwrap();
+        assert_eq!(longer, wavetable.resize());
+        assert_eq!(longer.truncate(), Some(wavetable));
+    }
+
+    #[test]
+    fn analysis()
+    {
+        let sine = Wavetable::<f64, 8>::from_fn(0.0, |m| if m == 0 {(1.0, 0.0)} else {(0.0, 0.0)});
+        assert!((sine.rms() - core::f64::consts::FRAC_1_SQRT_2).abs() < 1e-12);
+        assert!((sine.crest_factor() - core::f64::consts::SQRT_2).abs() < 1e-6);
+        assert_eq!(sine.thd(), 0.0);
+
+        let square = Wavetable::<f64, 256>::from_fn(0.5, |m| (0.0, if m % 2 == 0 {4.0/core::f64::consts::PI/(m + 1) as f64} else {0.0}));
+        assert!((square.energy() - 1.25).abs() < 1e-2);
+        assert!((square.zero_dc().thd() - (core::f64::consts::PI.powi(2)/8.0 - 1.0).sqrt()).abs() < 1e-2);
+        assert!((square.normalize_rms().rms() - core::f64::consts::FRAC_1_SQRT_2).abs() < 1e-12);
+        assert_eq!(Wavetable::<f64, 8>::from_fn(0.0, |_| (0.0, 0.0)).normalize_rms().rms(), 0.0);
+    }
+
+    #[test]
+    fn spectral_editing()
+    {
+        let saw = Wavetable::<f64, 8>::from_fn(0.25, |m| (0.0, 1.0/(m + 1) as f64));
+        let square = saw.remove_even();
+        assert_eq!(square.harmonic(2), Some((0.0, 0.0)));
+        assert_eq!(square.harmonic(3), saw.harmonic(3));
+        assert_eq!(saw.remove_odd() + square.zero_dc(), saw);
+        assert_eq!(saw - saw*0.5, saw*0.5);
+        assert_eq!(-saw + saw, saw.zero_dc()*0.0);
+        assert_eq!(saw.harmonic(0), None);
+        assert_eq!(saw.harmonic(9), None);
+
+        let (r, phi) = saw.polar(2).unwrap();
+        assert!((r - 0.5).abs() < 1e-12 && (phi - core::f64::consts::FRAC_PI_2).abs() < 1e-12);
+        let mut edited = saw;
+        edited.set_polar(2, 1.0, 0.0);
+        assert_eq!(edited.harmonic(2), Some((1.0, 0.0)));
+
+        let tilted = saw.zero_dc().tilt(6.0);
+        for n in 1..=8
+        {
+            let (r, _) = tilted.polar(n).unwrap();
+            assert!((r - 10f64.powf(0.3*(n as f64).log2())/n as f64).abs() < 1e-9);
+        }
+
+        let rotated = saw.rotate_phases(1.0);
+        let shifted = saw.phase_shift(0.5);
+        for n in 1..=8
+        {
+            assert!((rotated.polar(n).unwrap().0 - saw.polar(n).unwrap().0).abs() < 1e-12);
+            assert!((rotated.polar(n).unwrap().1 - saw.polar(n).unwrap().1 - 1.0).abs() < 1e-12);
+        }
+        for k in 0..16
+        {
+            let theta = k as f64*0.4;
+            assert!((shifted.waveform(theta, 8).unwrap() - saw.waveform(theta + 0.5, 8).unwrap()).abs() < 1e-12);
+        }
+
+        let (d, i, h) = (saw.derivative(), saw.integral(), saw.hilbert());
+        assert_eq!(d.integral(), saw.zero_dc());
+        assert_eq!(h.hilbert(), -saw.zero_dc());
+        for k in 0..16
+        {
+            let (theta, eps) = (k as f64*0.4, 1e-6);
+            let slope = (saw.waveform(theta + eps, 8).unwrap() - saw.waveform(theta - eps, 8).unwrap())/(2.0*eps);
+            assert!((d.waveform(theta, 8).unwrap() - slope).abs() < 1e-6);
+            let slope = (i.waveform(theta + eps, 8).unwrap() - i.waveform(theta - eps, 8).unwrap())/(2.0*eps);
+            assert!((saw.waveform(theta, 8).unwrap() - saw.a0() - slope).abs() < 1e-6);
+
+            let analytic = saw.analytic(theta, 8).unwrap();
+            assert!((analytic.re - saw.waveform(theta, 8).unwrap()).abs() < 1e-12);
+            assert!((analytic.im - h.waveform(theta, 8).unwrap()).abs() < 1e-12);
+        }
+
+        let normalized = saw.normalize();
+        assert!((normalized.peak() - 1.0).abs() < 1e-12);
+        assert!(normalized.peak() >= (0..1000).map(|k| normalized.waveform(k as f64*0.00628, 8).unwrap().abs()).fold(0.0, f64::max) - 1e-2);
+    }
+}